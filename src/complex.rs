@@ -0,0 +1,327 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Complex numbers as matrix elements
+//!
+//! A [Complex](Complex) is [Numeric](crate::matrix::Numeric) whenever its
+//! parts are, so `SMatrix<Complex<f64>, 8, 8>` supports the same arithmetic
+//! as a real matrix. Complex matrices additionally have `conj()`,
+//! `adjoint()` (the conjugate transpose), `is_hermitian()` and
+//! `is_unitary()`. A complex scalar multiplies a complex matrix with `*`.
+
+use crate::matrix::*;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+/// A complex number `re + im i`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Complex<T> {
+    /// The real part
+    pub re: T,
+    /// The imaginary part
+    pub im: T,
+}
+
+impl<T> Complex<T> {
+    /// Creates the complex number `re + im i`.
+    #[inline]
+    pub const fn new(re: T, im: T) -> Self {
+        Complex { re, im }
+    }
+}
+
+impl<T: Numeric<T>> Complex<T> {
+    /// Returns the complex conjugate `re - im i`.
+    #[inline]
+    pub fn conj(&self) -> Self
+    where
+        T: Neg<Output = T>,
+    {
+        Complex::new(self.re, -self.im)
+    }
+
+    /// Returns the squared absolute value `re² + im²`.
+    #[inline]
+    pub fn norm_sqr(&self) -> T {
+        self.re * self.re + self.im * self.im
+    }
+
+    /// Returns the imaginary unit `i`.
+    #[inline]
+    pub fn i() -> Self
+    where
+        T: One,
+    {
+        Complex::new(T::default(), T::one())
+    }
+}
+
+impl<T: Real> Complex<T> {
+    /// Creates a complex number from polar coordinates.
+    #[inline]
+    pub fn from_polar(r: T, theta: T) -> Self {
+        Complex::new(r * theta.cos(), r * theta.sin())
+    }
+
+    /// Returns the absolute value (modulus) computed without
+    /// intermediate overflow or underflow.
+    #[inline]
+    pub fn abs(&self) -> T {
+        self.re.hypot(self.im)
+    }
+
+    /// Returns the argument (phase) in `(-π, π]`.
+    #[inline]
+    pub fn arg(&self) -> T {
+        self.im.atan2(self.re)
+    }
+
+    /// Returns `e^self`.
+    #[inline]
+    pub fn exp(&self) -> Self {
+        Complex::from_polar(self.re.exp(), self.im)
+    }
+}
+
+impl<T: Numeric<T> + One> One for Complex<T> {
+    #[inline]
+    fn one() -> Self {
+        Complex::new(T::one(), T::default())
+    }
+}
+
+impl<T: Numeric<T> + FromUsize> FromUsize for Complex<T> {
+    #[inline]
+    fn from_usize(n: usize) -> Option<Self> {
+        T::from_usize(n).map(|re| Complex::new(re, T::default()))
+    }
+}
+
+impl<T: Numeric<T>> From<T> for Complex<T> {
+    #[inline]
+    fn from(re: T) -> Self {
+        Complex::new(re, T::default())
+    }
+}
+
+impl<T: Numeric<T>> Add for Complex<T> {
+    type Output = Complex<T>;
+
+    #[inline]
+    fn add(self, rhs: Complex<T>) -> Self::Output {
+        Complex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl<T: Numeric<T>> Sub for Complex<T> {
+    type Output = Complex<T>;
+
+    #[inline]
+    fn sub(self, rhs: Complex<T>) -> Self::Output {
+        Complex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl<T: Numeric<T>> Mul for Complex<T> {
+    type Output = Complex<T>;
+
+    #[inline]
+    fn mul(self, rhs: Complex<T>) -> Self::Output {
+        Complex::new(
+            self.re * rhs.re - self.im * rhs.im,
+            self.re * rhs.im + self.im * rhs.re,
+        )
+    }
+}
+
+impl<T: Real> Div for Complex<T> {
+    type Output = Complex<T>;
+
+    /// Smith's algorithm, which avoids the overflow of `|rhs|²`
+    #[inline]
+    fn div(self, rhs: Complex<T>) -> Self::Output {
+        let (a, b, c, d) = (self.re, self.im, rhs.re, rhs.im);
+        if c.abs() >= d.abs() {
+            let r = d / c;
+            let den = c + d * r;
+            Complex::new((a + b * r) / den, (b - a * r) / den)
+        } else {
+            let r = c / d;
+            let den = c * r + d;
+            Complex::new((a * r + b) / den, (b * r - a) / den)
+        }
+    }
+}
+
+impl<T: Numeric<T> + Neg<Output = T>> Neg for Complex<T> {
+    type Output = Complex<T>;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Complex::new(-self.re, -self.im)
+    }
+}
+
+impl<T: Numeric<T>> AddAssign for Complex<T> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Complex<T>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Numeric<T>> SubAssign for Complex<T> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Complex<T>) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Numeric<T>> MulAssign for Complex<T> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Complex<T>) {
+        *self = *self * rhs;
+    }
+}
+
+impl<T: Real> DivAssign for Complex<T> {
+    #[inline]
+    fn div_assign(&mut self, rhs: Complex<T>) {
+        *self = *self / rhs;
+    }
+}
+
+/// Formats as `re+imi` or `re-imi`. Width and precision apply to both parts.
+impl<T: Numeric<T> + Neg<Output = T> + PartialOrd + Display> Display for Complex<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.re, f)?;
+        if self.im < T::default() {
+            f.write_str("-")?;
+            Display::fmt(&-self.im, f)?;
+        } else {
+            f.write_str("+")?;
+            Display::fmt(&self.im, f)?;
+        }
+        f.write_str("i")
+    }
+}
+
+/// An error which can be returned when parsing a [Complex](Complex).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseComplexError {
+    input: String,
+}
+
+impl Display for ParseComplexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid complex number '{}'", self.input)
+    }
+}
+
+impl Error for ParseComplexError {}
+
+/// Parses `a`, `bi`, `a+bi` and `a-bi` (with `j` as an alternative to `i`
+/// and without whitespace), e.g. `"1.5-2e-3i"`, `"-i"` or `"4"`.
+impl<T: Numeric<T> + Neg<Output = T> + One + FromStr> FromStr for Complex<T> {
+    type Err = ParseComplexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseComplexError {
+            input: s.to_string(),
+        };
+        let parse = |part: &str| part.parse::<T>().map_err(|_| error());
+        let s = s.trim();
+        let body = match s.strip_suffix(|c| c == 'i' || c == 'j') {
+            Some(body) => body,
+            None => return parse(s).map(Complex::from),
+        };
+        // the sign that separates the parts, i.e., not a leading sign
+        // and not the sign of an exponent
+        let bytes = body.as_bytes();
+        let split = (1..bytes.len())
+            .rev()
+            .find(|&k| (bytes[k] == b'+' || bytes[k] == b'-') && !b"eE".contains(&bytes[k - 1]));
+        let (re, im) = match split {
+            Some(k) => (parse(&body[..k])?, &body[k..]),
+            None => (T::default(), body),
+        };
+        let im = match im {
+            "" | "+" => T::one(),
+            "-" => -T::one(),
+            im => parse(im.strip_prefix('+').unwrap_or(im))?,
+        };
+        Ok(Complex::new(re, im))
+    }
+}
+
+#[cfg(test)]
+mod complex_tests {
+    use super::*;
+
+    #[test]
+    fn test_arithmetic() {
+        let a = Complex::new(1, 2);
+        let b = Complex::new(3, -1);
+        assert_eq!(a + b, Complex::new(4, 1));
+        assert_eq!(a - b, Complex::new(-2, 3));
+        assert_eq!(a * b, Complex::new(5, 5));
+        assert_eq!(-a, Complex::new(-1, -2));
+        assert_eq!(Complex::<i32>::i() * Complex::i(), Complex::from(-1));
+        assert_eq!(a * a.conj(), Complex::from(a.norm_sqr()));
+        let mut c = a;
+        c *= b;
+        c -= a;
+        c += Complex::one();
+        assert_eq!(c, Complex::new(5, 3));
+    }
+
+    #[test]
+    fn test_division() {
+        let a = Complex::new(6.0, 2.0);
+        assert_eq!(a / Complex::new(2.0, -2.0), Complex::new(1.0, 2.0));
+        assert_eq!(a / Complex::new(0.5, 0.0), Complex::new(12.0, 4.0));
+        let mut c = a;
+        c /= Complex::new(0.0, 2.0);
+        assert_eq!(c, Complex::new(1.0, -3.0));
+        // |rhs|² would overflow
+        let big = Complex::new(1e300, 1e300);
+        assert_eq!(big / big, Complex::one());
+    }
+
+    #[test]
+    fn test_polar() {
+        use std::f64::consts::FRAC_PI_2;
+        let z = Complex::new(3.0, -4.0);
+        assert_eq!(z.abs(), 5.0);
+        let w = Complex::from_polar(z.abs(), z.arg());
+        assert!((w - z).abs() < 1e-14);
+        let i = Complex::new(0.0, FRAC_PI_2).exp();
+        assert!((i - Complex::i()).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Complex::new(1, -2).to_string(), "1-2i");
+        assert_eq!(Complex::new(-1.5, 0.25).to_string(), "-1.5+0.25i");
+        assert_eq!(format!("{:.2}", Complex::new(1.0f32, -1.0)), "1.00-1.00i");
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("1+2i".parse(), Ok(Complex::new(1, 2)));
+        assert_eq!("-3".parse(), Ok(Complex::new(-3, 0)));
+        assert_eq!("-i".parse(), Ok(Complex::new(0, -1)));
+        assert_eq!("j".parse(), Ok(Complex::new(0, 1)));
+        assert_eq!("2.5e-1-1e+2i".parse(), Ok(Complex::new(0.25, -100.0)));
+        assert_eq!("-1E2+4j".parse(), Ok(Complex::new(-100.0, 4.0)));
+        assert!("1+2".parse::<Complex<i32>>().is_err());
+        assert!("1+xi".parse::<Complex<i32>>().is_err());
+        let m: SMatrix<Complex<i32>, 2, 2> = "[1+i, 2; -i, 3-2i]".parse().unwrap();
+        assert_eq!(m[1][1], Complex::new(3, -2));
+    }
+}
@@ -0,0 +1,459 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! 2D and 3D geometric transforms
+//!
+//! Rotations are returned as `2 x 2` or `3 x 3` matrices that act on column
+//! vectors (`v' = R * v`); angles are in radians and positive angles rotate
+//! counterclockwise when looking down the axis towards the origin.
+//! Transforms in homogeneous coordinates are `3 x 3` (2D) and `4 x 4` (3D)
+//! matrices. The camera and projection matrices follow the OpenGL
+//! conventions (right-handed eye space looking down `-z`, clip space `z`
+//! in `[-1, 1]`).
+
+use crate::matrix::*;
+
+/// The order of the axes of an Euler angle rotation. The rotations are
+/// intrinsic: `XYZ` rotates about `x`, then about the new `y` and then about
+/// the new `z`, i.e., `R = Rx(a) * Ry(b) * Rz(c)`. This is the same as the
+/// extrinsic rotation in the reverse order (first about the fixed `z`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum EulerOrder {
+    /// Tait-Bryan angles `x-y-z`
+    XYZ,
+    /// Tait-Bryan angles `x-z-y`
+    XZY,
+    /// Tait-Bryan angles `y-x-z`
+    YXZ,
+    /// Tait-Bryan angles `y-z-x`
+    YZX,
+    /// Tait-Bryan angles `z-x-y`
+    ZXY,
+    /// Tait-Bryan angles `z-y-x` (yaw, pitch, roll)
+    ZYX,
+    /// Proper Euler angles `x-y-x`
+    XYX,
+    /// Proper Euler angles `x-z-x`
+    XZX,
+    /// Proper Euler angles `y-x-y`
+    YXY,
+    /// Proper Euler angles `y-z-y`
+    YZY,
+    /// Proper Euler angles `z-x-z`
+    ZXZ,
+    /// Proper Euler angles `z-y-z`
+    ZYZ,
+}
+
+#[inline]
+fn vector3<T: Copy, M: AsRef<[[T; 1]; 3]>>(v: &M) -> SVector<T, 3> {
+    SMatrix::new(*v.as_ref())
+}
+
+/// Returns the rotation by `angle` in the plane.
+#[inline]
+pub fn rotation_2d<T: Real>(angle: T) -> SMatrix<T, 2, 2> {
+    let (s, c) = (angle.sin(), angle.cos());
+    SMatrix::new([[c, -s], [s, c]])
+}
+
+/// Returns the rotation by `angle` about the `x` axis.
+#[inline]
+pub fn rotation_x<T: Real>(angle: T) -> SMatrix<T, 3, 3> {
+    let (s, c) = (angle.sin(), angle.cos());
+    let (zero, one) = (T::default(), T::one());
+    SMatrix::new([[one, zero, zero], [zero, c, -s], [zero, s, c]])
+}
+
+/// Returns the rotation by `angle` about the `y` axis.
+#[inline]
+pub fn rotation_y<T: Real>(angle: T) -> SMatrix<T, 3, 3> {
+    let (s, c) = (angle.sin(), angle.cos());
+    let (zero, one) = (T::default(), T::one());
+    SMatrix::new([[c, zero, s], [zero, one, zero], [-s, zero, c]])
+}
+
+/// Returns the rotation by `angle` about the `z` axis.
+#[inline]
+pub fn rotation_z<T: Real>(angle: T) -> SMatrix<T, 3, 3> {
+    let (s, c) = (angle.sin(), angle.cos());
+    let (zero, one) = (T::default(), T::one());
+    SMatrix::new([[c, -s, zero], [s, c, zero], [zero, zero, one]])
+}
+
+/// Returns the rotation by `angle` about `axis` which doesn't need to
+/// be normalized. A zero axis yields NaNs.
+pub fn rotation_axis_angle<T: Real, M: AsRef<[[T; 1]; 3]>>(axis: &M, angle: T) -> SMatrix<T, 3, 3> {
+    let k = vector3(axis).normalize();
+    let (x, y, z) = (k[0][0], k[1][0], k[2][0]);
+    let (s, c) = (angle.sin(), angle.cos());
+    let t = T::one() - c;
+    SMatrix::new([
+        [t * x * x + c, t * x * y - s * z, t * x * z + s * y],
+        [t * x * y + s * z, t * y * y + c, t * y * z - s * x],
+        [t * x * z - s * y, t * y * z + s * x, t * z * z + c],
+    ])
+}
+
+/// Returns the rotation for a rotation vector in Rodrigues form, i.e., the
+/// rotation about `r` by the angle `‖r‖`. The zero vector is the identity.
+#[inline]
+pub fn rotation_rodrigues<T: Real, M: AsRef<[[T; 1]; 3]>>(r: &M) -> SMatrix<T, 3, 3> {
+    let angle = vector3(r).norm();
+    if angle == T::default() {
+        SMatrix::one()
+    } else {
+        rotation_axis_angle(r, angle)
+    }
+}
+
+/// Returns the rotation for the Euler angles `a`, `b` and `c` about the
+/// axes in the given `order` (see [EulerOrder](EulerOrder)).
+pub fn rotation_euler<T: Real>(order: EulerOrder, a: T, b: T, c: T) -> SMatrix<T, 3, 3> {
+    use EulerOrder::*;
+    let x: fn(T) -> SMatrix<T, 3, 3> = rotation_x;
+    let y: fn(T) -> SMatrix<T, 3, 3> = rotation_y;
+    let z: fn(T) -> SMatrix<T, 3, 3> = rotation_z;
+    let (first, second, third) = match order {
+        XYZ => (x, y, z),
+        XZY => (x, z, y),
+        YXZ => (y, x, z),
+        YZX => (y, z, x),
+        ZXY => (z, x, y),
+        ZYX => (z, y, x),
+        XYX => (x, y, x),
+        XZX => (x, z, x),
+        YXY => (y, x, y),
+        YZY => (y, z, y),
+        ZXZ => (z, x, z),
+        ZYZ => (z, y, z),
+    };
+    first(a) * second(b) * third(c)
+}
+
+/// Returns the `4 x 4` homogeneous transform of a `3 x 3` linear map.
+#[inline]
+pub fn to_homogeneous<T: Real, M: AsRef<[[T; 3]; 3]>>(linear: &M) -> SMatrix<T, 4, 4> {
+    rigid_transform(linear, &SVector::<T, 3>::default())
+}
+
+/// Returns the homogeneous transform that first applies `rotation` and
+/// then translates by `translation`.
+pub fn rigid_transform<T: Real, R: AsRef<[[T; 3]; 3]>, V: AsRef<[[T; 1]; 3]>>(
+    rotation: &R,
+    translation: &V,
+) -> SMatrix<T, 4, 4> {
+    let (r, t) = (rotation.as_ref(), translation.as_ref());
+    let mut m = SMatrix::<T, 4, 4>::one();
+    for i in 0..3 {
+        m[i][..3].copy_from_slice(&r[i]);
+        m[i][3] = t[i][0];
+    }
+    m
+}
+
+/// Returns the homogeneous transform of the translation by `t`.
+#[inline]
+pub fn translation<T: Real, M: AsRef<[[T; 1]; 3]>>(t: &M) -> SMatrix<T, 4, 4> {
+    rigid_transform(&SMatrix::<T, 3, 3>::one(), t)
+}
+
+/// Returns the homogeneous transform of the scaling by `s` along the axes.
+#[inline]
+pub fn scaling<T: Real, M: AsRef<[[T; 1]; 3]>>(s: &M) -> SMatrix<T, 4, 4> {
+    let s = s.as_ref();
+    let mut m = SMatrix::<T, 4, 4>::one();
+    for i in 0..3 {
+        m[i][i] = s[i][0];
+    }
+    m
+}
+
+/// Returns the 2D homogeneous transform of the translation by `t`.
+#[inline]
+pub fn translation_2d<T: Real, M: AsRef<[[T; 1]; 2]>>(t: &M) -> SMatrix<T, 3, 3> {
+    let t = t.as_ref();
+    let mut m = SMatrix::<T, 3, 3>::one();
+    m[0][2] = t[0][0];
+    m[1][2] = t[1][0];
+    m
+}
+
+/// Returns the 2D homogeneous transform of the scaling by `s` along the axes.
+#[inline]
+pub fn scaling_2d<T: Real, M: AsRef<[[T; 1]; 2]>>(s: &M) -> SMatrix<T, 3, 3> {
+    let s = s.as_ref();
+    let mut m = SMatrix::<T, 3, 3>::one();
+    m[0][0] = s[0][0];
+    m[1][1] = s[1][0];
+    m
+}
+
+/// Returns the view matrix of a camera at `eye` looking at `target` with
+/// `up` pointing upwards (as `gluLookAt`). `eye` is mapped to the origin
+/// and `target` onto the negative `z` axis.
+pub fn look_at<T: Real, E: AsRef<[[T; 1]; 3]>, C: AsRef<[[T; 1]; 3]>, U: AsRef<[[T; 1]; 3]>>(
+    eye: &E,
+    target: &C,
+    up: &U,
+) -> SMatrix<T, 4, 4> {
+    let eye = vector3(eye);
+    let f = (vector3(target) - eye).normalize();
+    let s = f.cross(&vector3(up)).normalize();
+    let u = s.cross(&f);
+    let zero = T::default();
+    SMatrix::new([
+        [s[0][0], s[1][0], s[2][0], -s.dot(&eye)],
+        [u[0][0], u[1][0], u[2][0], -u.dot(&eye)],
+        [-f[0][0], -f[1][0], -f[2][0], f.dot(&eye)],
+        [zero, zero, zero, T::one()],
+    ])
+}
+
+/// Returns the perspective projection with the vertical field of view
+/// `fovy` (in radians), the width to height ratio `aspect` and the
+/// distances `near` and `far` of the clipping planes (as `gluPerspective`).
+pub fn perspective<T: Real>(fovy: T, aspect: T, near: T, far: T) -> SMatrix<T, 4, 4> {
+    let two = T::from_f64(2.0);
+    let f = T::one() / (fovy / two).tan();
+    let zero = T::default();
+    SMatrix::new([
+        [f / aspect, zero, zero, zero],
+        [zero, f, zero, zero],
+        [
+            zero,
+            zero,
+            (far + near) / (near - far),
+            two * far * near / (near - far),
+        ],
+        [zero, zero, -T::one(), zero],
+    ])
+}
+
+/// Returns the orthographic projection of the given box onto the cube
+/// `[-1, 1]³` (as `glOrtho`).
+pub fn orthographic<T: Real>(
+    left: T,
+    right: T,
+    bottom: T,
+    top: T,
+    near: T,
+    far: T,
+) -> SMatrix<T, 4, 4> {
+    let two = T::from_f64(2.0);
+    let zero = T::default();
+    SMatrix::new([
+        [
+            two / (right - left),
+            zero,
+            zero,
+            -(right + left) / (right - left),
+        ],
+        [
+            zero,
+            two / (top - bottom),
+            zero,
+            -(top + bottom) / (top - bottom),
+        ],
+        [
+            zero,
+            zero,
+            -two / (far - near),
+            -(far + near) / (far - near),
+        ],
+        [zero, zero, zero, T::one()],
+    ])
+}
+
+/// Transforms the point `p` by the homogeneous transform `m`, including
+/// the division by `w` for projective transforms.
+pub fn transform_point<T: Real, M: AsRef<[[T; 4]; 4]>, P: AsRef<[[T; 1]; 3]>>(
+    m: &M,
+    p: &P,
+) -> SVector<T, 3> {
+    let (m, p) = (m.as_ref(), p.as_ref());
+    let mut h = [T::default(); 4];
+    for (h_i, row) in h.iter_mut().zip(m.iter()) {
+        *h_i = row[0] * p[0][0] + row[1] * p[1][0] + row[2] * p[2][0] + row[3];
+    }
+    let w = h[3];
+    SMatrix::new([[h[0] / w], [h[1] / w], [h[2] / w]])
+}
+
+/// Transforms the direction `d` by the homogeneous transform `m`, i.e.,
+/// by its linear part only (translations don't affect directions).
+pub fn transform_direction<T: Real, M: AsRef<[[T; 4]; 4]>, D: AsRef<[[T; 1]; 3]>>(
+    m: &M,
+    d: &D,
+) -> SVector<T, 3> {
+    let (m, d) = (m.as_ref(), d.as_ref());
+    let mut r = SVector::<T, 3>::default();
+    for i in 0..3 {
+        r[i][0] = m[i][0] * d[0][0] + m[i][1] * d[1][0] + m[i][2] * d[2][0];
+    }
+    r
+}
+
+/// Returns the inverse of a rigid transform `[R t; 0 1]` (a rotation
+/// followed by a translation) which is `[Rᵀ -Rᵀt; 0 1]`. The result is
+/// meaningless if `m` isn't rigid.
+pub fn invert_rigid<T: Real, M: AsRef<[[T; 4]; 4]>>(m: &M) -> SMatrix<T, 4, 4> {
+    let m = m.as_ref();
+    let mut inv = SMatrix::<T, 4, 4>::one();
+    for i in 0..3 {
+        let mut t = T::default();
+        for j in 0..3 {
+            inv[i][j] = m[j][i];
+            t -= m[j][i] * m[j][3];
+        }
+        inv[i][3] = t;
+    }
+    inv
+}
+
+#[cfg(test)]
+mod geometry_tests {
+    use super::*;
+    use crate::assert_matrix_abs_diff_eq;
+    use std::f64::consts::{FRAC_PI_2, FRAC_PI_3, FRAC_PI_4, PI};
+
+    const EPS: f64 = 1e-12;
+
+    #[test]
+    fn test_elementary_rotations() {
+        assert_matrix_abs_diff_eq!(
+            rotation_2d(FRAC_PI_2) * svector![1.0, 0.0],
+            svector![0.0, 1.0],
+            EPS
+        );
+        let x = svector![1.0, 0.0, 0.0];
+        let y = svector![0.0, 1.0, 0.0];
+        let z = svector![0.0, 0.0, 1.0];
+        assert_matrix_abs_diff_eq!(rotation_x(FRAC_PI_2) * y, z, EPS);
+        assert_matrix_abs_diff_eq!(rotation_y(FRAC_PI_2) * z, x, EPS);
+        assert_matrix_abs_diff_eq!(rotation_z(FRAC_PI_2) * x, y, EPS);
+        let r = rotation_z(0.3f32) * rotation_x(-1.1);
+        assert_matrix_abs_diff_eq!(r.trans() * r, SMatrix::one(), 1e-6);
+    }
+
+    #[test]
+    fn test_axis_angle_rodrigues() {
+        let axis = svector![0.0f64, 0.0, 3.0];
+        assert_matrix_abs_diff_eq!(rotation_axis_angle(&axis, 0.7), rotation_z(0.7), EPS);
+        assert_matrix_abs_diff_eq!(
+            rotation_axis_angle(&hmatrix![-2.0f64; 0.0; 0.0], 0.7),
+            rotation_x(-0.7),
+            EPS
+        );
+        // 120° about the diagonal permutes the axes
+        let diagonal = svector![1.0, 1.0, 1.0];
+        let r = rotation_axis_angle(&diagonal, 2.0 * PI / 3.0);
+        assert_matrix_abs_diff_eq!(
+            r,
+            smatrix![0.0, 0.0, 1.0; 1.0, 0.0, 0.0; 0.0, 1.0, 0.0],
+            EPS
+        );
+        let rodrigues = (2.0 * PI / 3.0) * diagonal.normalize();
+        assert_matrix_abs_diff_eq!(rotation_rodrigues(&rodrigues), r, EPS);
+        assert_eq!(rotation_rodrigues(&svector![0.0, 0.0, 0.0]), SMatrix::one());
+    }
+
+    #[test]
+    fn test_euler() {
+        let (a, b, c) = (0.1f64, -0.4, 1.3);
+        assert_matrix_abs_diff_eq!(
+            rotation_euler(EulerOrder::ZYX, a, b, c),
+            rotation_z(a) * rotation_y(b) * rotation_x(c),
+            EPS
+        );
+        assert_matrix_abs_diff_eq!(
+            rotation_euler(EulerOrder::ZXZ, a, b, c),
+            rotation_z(a) * rotation_x(b) * rotation_z(c),
+            EPS
+        );
+        assert_matrix_abs_diff_eq!(
+            rotation_euler(EulerOrder::YXY, a, 0.0, c),
+            rotation_y(a + c),
+            EPS
+        );
+    }
+
+    #[test]
+    fn test_homogeneous() {
+        let r = rotation_z(FRAC_PI_2);
+        let t = svector![1.0, 2.0, 3.0];
+        let m = rigid_transform(&r, &t);
+        assert_eq!(m, translation(&t) * to_homogeneous(&r));
+        let p = svector![1.0, 0.0, 0.0];
+        assert_matrix_abs_diff_eq!(transform_point(&m, &p), svector![1.0, 3.0, 3.0], EPS);
+        assert_matrix_abs_diff_eq!(transform_direction(&m, &p), svector![0.0, 1.0, 0.0], EPS);
+        let s = scaling(&svector![2.0, 3.0, 4.0]);
+        assert_eq!(transform_point(&s, &t), svector![2.0, 6.0, 12.0]);
+        let m2 = translation_2d(&svector![1.0, -1.0]) * scaling_2d(&svector![2.0, 2.0]);
+        assert_eq!(m2 * svector![1.0, 1.0, 1.0], svector![3.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn test_invert_rigid() {
+        let r = rotation_euler(EulerOrder::XYZ, 0.3, FRAC_PI_3, -2.0);
+        let m = rigid_transform(&r, &svector![-4.0, 0.5, 7.0]);
+        assert_matrix_abs_diff_eq!(invert_rigid(&m) * m, SMatrix::one(), EPS);
+        assert_matrix_abs_diff_eq!(m * invert_rigid(&m.to_heap()), SMatrix::one(), EPS);
+    }
+
+    #[test]
+    fn test_look_at() {
+        let eye = svector![1.0f64, 2.0, 3.0];
+        let target = svector![1.0, 2.0, -7.0];
+        let view = look_at(&eye, &target, &svector![0.0, 1.0, 0.0]);
+        assert_matrix_abs_diff_eq!(transform_point(&view, &eye), svector![0.0, 0.0, 0.0], EPS);
+        assert_matrix_abs_diff_eq!(
+            transform_point(&view, &target),
+            svector![0.0, 0.0, -10.0],
+            EPS
+        );
+        let side = look_at(
+            &svector![0.0f64, 0.0, 0.0],
+            &svector![1.0, 0.0, 0.0],
+            &svector![0.0, 0.0, 1.0],
+        );
+        assert_matrix_abs_diff_eq!(
+            transform_direction(&side, &svector![0.0, 0.0, 1.0]),
+            svector![0.0, 1.0, 0.0],
+            EPS
+        );
+        assert_matrix_abs_diff_eq!(invert_rigid(&side) * side, SMatrix::one(), EPS);
+    }
+
+    #[test]
+    fn test_projections() {
+        let p = perspective(FRAC_PI_2, 2.0, 1.0, 10.0);
+        assert_matrix_abs_diff_eq!(
+            transform_point(&p, &svector![2.0, 1.0, -1.0]),
+            svector![1.0, 1.0, -1.0],
+            EPS
+        );
+        assert_matrix_abs_diff_eq!(
+            transform_point(&p, &svector![0.0, 0.0, -10.0]),
+            svector![0.0, 0.0, 1.0],
+            EPS
+        );
+        let o = orthographic(-2.0f64, 2.0, 0.0, 1.0, 1.0, 3.0);
+        assert_matrix_abs_diff_eq!(
+            transform_point(&o, &svector![-2.0, 0.0, -1.0]),
+            svector![-1.0, -1.0, -1.0],
+            EPS
+        );
+        assert_matrix_abs_diff_eq!(
+            transform_point(&o, &svector![2.0, 1.0, -3.0]),
+            svector![1.0, 1.0, 1.0],
+            EPS
+        );
+        let p32 = perspective(FRAC_PI_4 as f32, 1.0, 0.1, 100.0);
+        assert!((p32[1][1] - 2.414_213_5).abs() < 1e-6);
+    }
+}
@@ -10,18 +10,52 @@
 #![crate_name = "const_matrix"]
 #![warn(missing_docs)]
 #![warn(rust_2018_idioms)]
+// the tests deliberately exercise every operand form of the operators
+#![cfg_attr(test, allow(clippy::op_ref, clippy::clone_on_copy))]
 
+#[macro_use]
+mod macros;
+pub mod complex;
+pub mod geometry;
 pub mod matrix;
 mod matrix_add;
 mod matrix_add_assign;
+mod matrix_broadcast;
+mod matrix_cast;
+mod matrix_complex;
+mod matrix_const;
+mod matrix_display;
+mod matrix_eq;
 mod matrix_index;
+mod matrix_kronecker;
+mod matrix_map;
 mod matrix_mul;
 mod matrix_mul_assign;
 mod matrix_neg;
+mod matrix_norm;
+mod matrix_overflow;
+mod matrix_parse;
+mod matrix_permute;
+mod matrix_predicates;
+mod matrix_reduce;
+mod matrix_scalar;
+#[cfg(feature = "serde")]
+mod matrix_serde;
+mod matrix_shape;
+mod matrix_square;
+mod matrix_structured;
 mod matrix_sub;
 mod matrix_sub_assign;
+mod matrix_vector;
+mod matrix_widening;
 mod mul_to_heap;
 mod mul_to_stack;
+pub mod npy;
+pub mod permutation;
+pub mod quaternion;
+pub mod random;
+mod scalar_div_inplace;
+mod scalar_mul_complex;
 mod scalar_mul_f32;
 mod scalar_mul_f64;
 mod scalar_mul_i128;
@@ -30,6 +64,15 @@ mod scalar_mul_i32;
 mod scalar_mul_i64;
 mod scalar_mul_i8;
 mod scalar_mul_inplace;
+mod scalar_mul_isize;
+mod scalar_mul_u128;
+mod scalar_mul_u16;
+mod scalar_mul_u32;
+mod scalar_mul_u64;
+mod scalar_mul_u8;
+mod scalar_mul_usize;
+#[cfg(feature = "serde")]
+pub mod serde_flat;
 
 #[cfg(test)]
 mod lib_tests {
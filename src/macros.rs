@@ -0,0 +1,161 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Macros for matrix literals in MATLAB-like syntax
+
+/// Creates an [SMatrix](crate::matrix::SMatrix) from a MATLAB-like literal
+/// where rows are separated by `;` and the elements of a row by `,`.
+/// The number of rows and columns is inferred at compile time and rows
+/// of unequal length are a compile error.
+///
+/// ```
+/// use const_matrix::smatrix;
+/// use const_matrix::matrix::SMatrix;
+///
+/// let m: SMatrix<i32, 2, 3> = smatrix![1, 2, 3; 4, 5, 6];
+/// assert_eq!(m[1][2], 6);
+/// ```
+///
+/// ```compile_fail
+/// let ragged = const_matrix::smatrix![1, 2, 3; 4, 5];
+/// ```
+#[macro_export]
+macro_rules! smatrix {
+    ($($($x:expr),+ $(,)?);+ $(;)?) => {
+        $crate::matrix::SMatrix::new([$([$($x),+]),+])
+    };
+}
+
+/// Same as [smatrix!](crate::smatrix), i.e., creates a stack-allocated matrix.
+#[macro_export]
+macro_rules! matrix {
+    ($($tokens:tt)*) => {
+        $crate::smatrix![$($tokens)*]
+    };
+}
+
+/// Creates an [HMatrix](crate::matrix::HMatrix) from a MATLAB-like literal
+/// where rows are separated by `;` and the elements of a row by `,`.
+/// The elements are written directly into the heap-allocated matrix. The
+/// number of rows and columns is inferred at compile time and rows of
+/// unequal length are a compile error.
+///
+/// ```
+/// use const_matrix::hmatrix;
+/// use const_matrix::matrix::HMatrix;
+///
+/// let m: HMatrix<f64, 3, 2> = hmatrix![1.0, 2.0; 3.0, 4.0; 5.0, 6.0];
+/// assert_eq!(m[2][0], 5.0);
+/// ```
+///
+/// ```compile_fail
+/// let ragged = const_matrix::hmatrix![1, 2; 3, 4, 5];
+/// ```
+#[macro_export]
+macro_rules! hmatrix {
+    ($($($x:expr),+ $(,)?);+ $(;)?) => {{
+        // the zero-sized shape array lets the compiler infer (and check) the
+        // dimensions without putting the elements on the stack
+        let mut m = $crate::matrix::MF::__new_heap_with_shape([$([$($crate::__unit!($x)),+]),+]);
+        {
+            let mut rows = ::std::convert::AsMut::as_mut(&mut m).iter_mut();
+            $(
+                let mut cells = rows.next().unwrap().iter_mut();
+                $(*cells.next().unwrap() = $x;)+
+            )+
+        }
+        m
+    }};
+}
+
+/// Creates a stack-allocated column vector, i.e., an
+/// [SMatrix](crate::matrix::SMatrix) with a single column.
+///
+/// ```
+/// use const_matrix::svector;
+/// use const_matrix::matrix::SMatrix;
+///
+/// let v: SMatrix<f32, 3, 1> = svector![1.0, 2.0, 3.0];
+/// assert_eq!(v[2][0], 3.0);
+/// ```
+#[macro_export]
+macro_rules! svector {
+    ($($x:expr),+ $(,)?) => {
+        $crate::matrix::SMatrix::new([$([$x]),+])
+    };
+}
+
+/// Creates a stack-allocated row vector, i.e., an
+/// [SMatrix](crate::matrix::SMatrix) with a single row.
+///
+/// ```
+/// use const_matrix::rvector;
+/// use const_matrix::matrix::SMatrix;
+///
+/// let v: SMatrix<f32, 1, 3> = rvector![1.0, 2.0, 3.0];
+/// assert_eq!(v[0][2], 3.0);
+/// ```
+#[macro_export]
+macro_rules! rvector {
+    ($($x:expr),+ $(,)?) => {
+        $crate::matrix::SMatrix::new([[$($x),+]])
+    };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __unit {
+    ($x:expr) => {
+        ()
+    };
+}
+
+#[cfg(test)]
+mod macros_tests {
+    use crate::matrix::*;
+
+    #[test]
+    fn test_smatrix() {
+        let a = smatrix![1, 2, 3; 4, 5, 6];
+        assert_eq!(a, SMatrix::new([[1, 2, 3], [4, 5, 6]]));
+        let b: SMatrix<f64, 2, 2> = smatrix![
+            1.0, 2.0;
+            3.0, 4.0;
+        ];
+        assert_eq!(b, SMatrix::new([[1.0, 2.0], [3.0, 4.0]]));
+        let c = matrix![7];
+        assert_eq!(c, SMatrix::new([[7]]));
+        let x = 2;
+        let d = smatrix![x * 2, -x; x + 1, 0];
+        assert_eq!(d, SMatrix::new([[4, -2], [3, 0]]));
+    }
+
+    #[test]
+    fn test_hmatrix() {
+        let a = hmatrix![1, 2, 3; 4, 5, 6];
+        assert_eq!(a, HMatrix::new([[1, 2, 3], [4, 5, 6]]));
+        let b: HMatrix<f32, 3, 1> = hmatrix![1.0; 2.0; 3.0,];
+        assert_eq!(b, SMatrix::new([[1.0], [2.0], [3.0]]));
+        let mut calls = Vec::new();
+        let mut next = |i: i64| {
+            calls.push(i);
+            i
+        };
+        let c = hmatrix![next(1), next(2); next(3), next(4)];
+        assert_eq!(c, SMatrix::new([[1, 2], [3, 4]]));
+        assert_eq!(calls, vec![1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn test_vectors() {
+        let v = svector![1, 2, 3];
+        assert_eq!(v, SMatrix::new([[1], [2], [3]]));
+        let r = rvector![1, 2, 3,];
+        assert_eq!(r, SMatrix::new([[1, 2, 3]]));
+        assert_eq!(v.trans(), r);
+    }
+}
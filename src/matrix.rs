@@ -10,11 +10,18 @@
 //! using const generics. Both matrix types are fully interoperable with
 //! each other.
 
-use std::fmt::Debug;
+use std::convert::TryFrom;
+use std::fmt::{Debug, Display};
 use std::marker::PhantomData;
-use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
 use std::str::FromStr;
 
+pub use crate::matrix_cast::{AsPrimitive, CheckedCast, TryCastError};
+pub use crate::matrix_kronecker::{kronecker, kronecker_heap};
+pub use crate::matrix_parse::ParseMatrixError;
+pub use crate::matrix_shape::{hstack, hstack_heap, vstack, vstack_heap};
+pub use crate::matrix_widening::{MulWidening, QuantizedInt};
+
 /// All types for which the operators `+`, `+=`, `*`, `*=`,
 /// `-` (unary negation), `-` (binary minus) and `-=` are
 /// defined and where the output type of `+=`, `*=` and `-=`
@@ -43,10 +50,252 @@ impl<
 {
 }
 
-/// All types which are `Copy` and `Default` in addition
-/// to being [Arithmetic](Arithmetic).
-pub trait Numeric<T>: Copy + Default + Arithmetic<T> {}
-impl<T: Copy + Default + Arithmetic<T>> Numeric<T> for T {}
+/// All types which are `Copy` and `Default` and for which the
+/// operators `+`, `+=`, `*`, `*=`, `-` (binary minus) and `-=`
+/// are defined with output type `T`. Unlike [Arithmetic](Arithmetic),
+/// unary negation isn't required, so that the unsigned integers are
+/// `Numeric` as well.
+pub trait Numeric<T>:
+    Copy
+    + Default
+    + Add<Output = T>
+    + AddAssign
+    + Mul<Output = T>
+    + MulAssign
+    + Sub<Output = T>
+    + SubAssign
+{
+}
+
+impl<
+        T: Copy
+            + Default
+            + Add<Output = T>
+            + AddAssign
+            + Mul<Output = T>
+            + MulAssign
+            + Sub<Output = T>
+            + SubAssign,
+    > Numeric<T> for T
+{
+}
+
+/// Types which have a multiplicative identity `one()`, i.e., the
+/// counterpart to the additive identity `Default::default()`.
+pub trait One {
+    /// Returns the multiplicative identity.
+    fn one() -> Self;
+}
+
+macro_rules! impl_one {
+    ($($t:ty, $one:expr);*) => {
+        $(impl One for $t {
+            #[inline]
+            fn one() -> Self {
+                $one
+            }
+        })*
+    };
+}
+
+impl_one!(i8, 1; i16, 1; i32, 1; i64, 1; i128, 1; isize, 1;
+    u8, 1; u16, 1; u32, 1; u64, 1; u128, 1; usize, 1; f32, 1.0; f64, 1.0);
+
+/// Types which can represent a count of elements, e.g. to compute means.
+pub trait FromUsize: Sized {
+    /// Converts `n` to `Self`, or returns `None` if it is out of range.
+    /// Floats round to the nearest representable value.
+    fn from_usize(n: usize) -> Option<Self>;
+}
+
+macro_rules! impl_from_usize {
+    (int: $($t:ty),*; float: $($f:ty),*) => {
+        $(impl FromUsize for $t {
+            #[inline]
+            fn from_usize(n: usize) -> Option<Self> {
+                std::convert::TryFrom::try_from(n).ok()
+            }
+        })*
+        $(impl FromUsize for $f {
+            #[inline]
+            fn from_usize(n: usize) -> Option<Self> {
+                Some(n as $f)
+            }
+        })*
+    };
+}
+
+impl_from_usize!(int: i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize;
+    float: f32, f64);
+
+/// The floating-point types `f32` and `f64` which, in addition to
+/// being [Numeric](Numeric), support negation, division, ordering and the usual
+/// elementary functions.
+pub trait Real:
+    Numeric<Self>
+    + One
+    + Neg<Output = Self>
+    + PartialOrd
+    + Div<Output = Self>
+    + DivAssign
+    + Debug
+    + Display
+{
+    /// Machine epsilon
+    const EPSILON: Self;
+    /// Smallest positive normal value
+    const MIN_POSITIVE: Self;
+    /// Largest finite value
+    const MAX: Self;
+    /// Archimedes' constant π
+    const PI: Self;
+
+    /// Converts an `f64` to `Self` (rounding if necessary).
+    fn from_f64(x: f64) -> Self;
+    /// Converts `self` to an `f64`.
+    fn to_f64(self) -> f64;
+    /// Absolute value
+    fn abs(self) -> Self;
+    /// Square root
+    fn sqrt(self) -> Self;
+    /// Sine (in radians)
+    fn sin(self) -> Self;
+    /// Cosine (in radians)
+    fn cos(self) -> Self;
+    /// Tangent (in radians)
+    fn tan(self) -> Self;
+    /// Arcsine (in radians)
+    fn asin(self) -> Self;
+    /// Arccosine (in radians)
+    fn acos(self) -> Self;
+    /// Four quadrant arctangent of `self` (y) and `x` (in radians)
+    fn atan2(self, x: Self) -> Self;
+    /// Exponential function
+    fn exp(self) -> Self;
+    /// Natural logarithm
+    fn ln(self) -> Self;
+    /// `sqrt(self² + other²)` without undue overflow or underflow
+    fn hypot(self, other: Self) -> Self;
+    /// Maximum of two numbers, ignoring NaN
+    fn max(self, other: Self) -> Self;
+    /// Minimum of two numbers, ignoring NaN
+    fn min(self, other: Self) -> Self;
+    /// Returns `true` if this value is NaN
+    fn is_nan(self) -> bool;
+    /// Returns `true` if this value is neither infinite nor NaN
+    fn is_finite(self) -> bool;
+    /// The largest value less than `self` (NaN and -∞ are returned as is)
+    fn next_down(self) -> Self;
+}
+
+macro_rules! impl_real {
+    ($t:ident) => {
+        impl Real for $t {
+            const EPSILON: Self = $t::EPSILON;
+            const MIN_POSITIVE: Self = $t::MIN_POSITIVE;
+            const MAX: Self = $t::MAX;
+            const PI: Self = std::$t::consts::PI;
+
+            #[inline]
+            fn from_f64(x: f64) -> Self {
+                x as $t
+            }
+            #[inline]
+            fn to_f64(self) -> f64 {
+                self as f64
+            }
+            #[inline]
+            fn abs(self) -> Self {
+                $t::abs(self)
+            }
+            #[inline]
+            fn sqrt(self) -> Self {
+                $t::sqrt(self)
+            }
+            #[inline]
+            fn sin(self) -> Self {
+                $t::sin(self)
+            }
+            #[inline]
+            fn cos(self) -> Self {
+                $t::cos(self)
+            }
+            #[inline]
+            fn tan(self) -> Self {
+                $t::tan(self)
+            }
+            #[inline]
+            fn asin(self) -> Self {
+                $t::asin(self)
+            }
+            #[inline]
+            fn acos(self) -> Self {
+                $t::acos(self)
+            }
+            #[inline]
+            fn atan2(self, x: Self) -> Self {
+                $t::atan2(self, x)
+            }
+            #[inline]
+            fn exp(self) -> Self {
+                $t::exp(self)
+            }
+            #[inline]
+            fn ln(self) -> Self {
+                $t::ln(self)
+            }
+            #[inline]
+            fn hypot(self, other: Self) -> Self {
+                $t::hypot(self, other)
+            }
+            #[inline]
+            fn max(self, other: Self) -> Self {
+                $t::max(self, other)
+            }
+            #[inline]
+            fn min(self, other: Self) -> Self {
+                $t::min(self, other)
+            }
+            #[inline]
+            fn is_nan(self) -> bool {
+                $t::is_nan(self)
+            }
+            #[inline]
+            fn next_down(self) -> Self {
+                // $t::next_down needs Rust 1.86
+                if self.is_nan() || self == $t::NEG_INFINITY {
+                    self
+                } else if self == 0.0 {
+                    -$t::from_bits(1)
+                } else if self > 0.0 {
+                    $t::from_bits(self.to_bits() - 1)
+                } else {
+                    $t::from_bits(self.to_bits() + 1)
+                }
+            }
+            #[inline]
+            fn is_finite(self) -> bool {
+                $t::is_finite(self)
+            }
+        }
+    };
+}
+
+impl_real!(f32);
+impl_real!(f64);
+
+/// The identity matrix is the multiplicative identity of square matrices
+/// so that block matrices with `SMatrix` elements have a `one()` as well.
+impl<T: Numeric<T> + One, const N: usize> One for SMatrix<T, N, N> {
+    #[inline]
+    fn one() -> Self {
+        let mut m = MF::<T, N, N>::new_stack();
+        for i in 0..N {
+            m[i][i] = T::one();
+        }
+        m
+    }
+}
 
 /// A matrix which is allocated on the stack.
 /// An `SMatrix` is itself [Numeric](Numeric), so that its
@@ -54,24 +303,49 @@ impl<T: Copy + Default + Arithmetic<T>> Numeric<T> for T {}
 /// theoretically could contain `SMatrices` themselves up to
 /// arbitrarily deep finite nesting levels..
 #[derive(Debug, Copy, Clone)]
-pub struct SMatrix<T: Numeric<T>, const ROWS: usize, const COLS: usize> {
+pub struct SMatrix<T, const ROWS: usize, const COLS: usize> {
     a: [[T; COLS]; ROWS],
 }
 
 /// A matrix which is allocated on the heap.
 #[derive(Debug, Clone)]
-pub struct HMatrix<T: Numeric<T>, const ROWS: usize, const COLS: usize> {
+pub struct HMatrix<T, const ROWS: usize, const COLS: usize> {
     a: Box<[[T; COLS]; ROWS]>,
 }
 
-// new(), to_heap() and crate-internal array access for SMatrix
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+/// A column vector which is allocated on the stack.
+pub type SVector<T, const N: usize> = SMatrix<T, N, 1>;
+
+/// A column vector which is allocated on the heap.
+pub type HVector<T, const N: usize> = HMatrix<T, N, 1>;
+
+/// A row vector which is allocated on the stack.
+pub type SRowVector<T, const N: usize> = SMatrix<T, 1, N>;
+
+/// A row vector which is allocated on the heap.
+pub type HRowVector<T, const N: usize> = HMatrix<T, 1, N>;
+
+// new() and crate-internal array access for SMatrix
+impl<'a, T, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
     /// Creates a new stack-allocated matrix from the given initial values.
     #[inline]
-    pub fn new(array: [[T; COLS]; ROWS]) -> Self {
+    pub const fn new(array: [[T; COLS]; ROWS]) -> Self {
         SMatrix { a: array }
     }
 
+    #[inline]
+    pub(crate) const fn array(&'a self) -> &'a [[T; COLS]; ROWS] {
+        &self.a
+    }
+
+    #[inline]
+    pub(crate) fn array_mut(&'a mut self) -> &'a mut [[T; COLS]; ROWS] {
+        &mut self.a
+    }
+}
+
+// trans() and to_heap() for SMatrix
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
     /// Creates a stack-allocated transpose of this matrix.
     #[inline]
     pub fn trans(&self) -> SMatrix<T, COLS, ROWS> {
@@ -87,20 +361,34 @@ impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, C
         heap_copy.array_mut().copy_from_slice(self.array());
         heap_copy
     }
+}
+
+// crate-internal construction and array access for HMatrix
+impl<'a, T, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Creates a heap-allocated matrix from its rows without
+    /// putting the whole matrix on the stack.
+    #[inline]
+    pub(crate) fn from_rows<I: Iterator<Item = [T; COLS]>>(rows: I) -> Self {
+        let rows: Box<[[T; COLS]]> = rows.collect();
+        match Box::<[[T; COLS]; ROWS]>::try_from(rows) {
+            Ok(a) => HMatrix { a },
+            Err(_) => panic!("expected exactly {} rows", ROWS),
+        }
+    }
 
     #[inline]
     pub(crate) fn array(&'a self) -> &'a [[T; COLS]; ROWS] {
-        &self.a
+        self.a.as_ref()
     }
 
     #[inline]
     pub(crate) fn array_mut(&'a mut self) -> &'a mut [[T; COLS]; ROWS] {
-        &mut self.a
+        self.a.as_mut()
     }
 }
 
-// new(), to_stack() and crate-internal array access for HMatrix
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+// new(), trans() and to_stack() for HMatrix
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
     /// Creates a new stack-allocated matrix from the given initial values.
     #[inline]
     pub fn new(array: [[T; COLS]; ROWS]) -> Self {
@@ -124,15 +412,37 @@ impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, C
         stack_copy.array_mut().copy_from_slice(self.array());
         stack_copy
     }
+}
 
+impl<T, const ROWS: usize, const COLS: usize> AsRef<[[T; COLS]; ROWS]> for SMatrix<T, ROWS, COLS> {
+    /// Returns the elements of this matrix as a row-major array.
     #[inline]
-    pub(crate) fn array(&'a self) -> &'a [[T; COLS]; ROWS] {
-        self.a.as_ref()
+    fn as_ref(&self) -> &[[T; COLS]; ROWS] {
+        self.array()
     }
+}
 
+impl<T, const ROWS: usize, const COLS: usize> AsRef<[[T; COLS]; ROWS]> for HMatrix<T, ROWS, COLS> {
+    /// Returns the elements of this matrix as a row-major array.
     #[inline]
-    pub(crate) fn array_mut(&'a mut self) -> &'a mut [[T; COLS]; ROWS] {
-        self.a.as_mut()
+    fn as_ref(&self) -> &[[T; COLS]; ROWS] {
+        self.array()
+    }
+}
+
+impl<T, const ROWS: usize, const COLS: usize> AsMut<[[T; COLS]; ROWS]> for SMatrix<T, ROWS, COLS> {
+    /// Returns the elements of this matrix as a mutable row-major array.
+    #[inline]
+    fn as_mut(&mut self) -> &mut [[T; COLS]; ROWS] {
+        self.array_mut()
+    }
+}
+
+impl<T, const ROWS: usize, const COLS: usize> AsMut<[[T; COLS]; ROWS]> for HMatrix<T, ROWS, COLS> {
+    /// Returns the elements of this matrix as a mutable row-major array.
+    #[inline]
+    fn as_mut(&mut self) -> &mut [[T; COLS]; ROWS] {
+        self.array_mut()
     }
 }
 
@@ -171,6 +481,13 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> MF<T, ROWS, COLS> {
         HMatrix { a: box_ }
     }
 
+    // Used by the `hmatrix!` macro to infer ROWS and COLS from a zero-sized array
+    #[doc(hidden)]
+    #[inline]
+    pub fn __new_heap_with_shape(_shape: [[(); COLS]; ROWS]) -> HMatrix<T, ROWS, COLS> {
+        MF::<T, ROWS, COLS>::new_heap()
+    }
+
     /// Create an identity matrix on the stack.
     #[inline]
     pub fn unit_stack() -> SMatrix<T, ROWS, ROWS>
@@ -0,0 +1,202 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Row and column broadcasting
+//!
+//! The `*_row_broadcast` methods combine every row of the matrix with a
+//! row vector (`1 x COLS`) and the `*_col_broadcast` methods combine every
+//! column with a column vector (`ROWS x 1`), element by element. E.g.,
+//! `m.sub_row_broadcast(&col_means)` centers the columns of `m`.
+//! `scale_rows(&d)` and `scale_cols(&d)` compute `diag(d) * m` and
+//! `m * diag(d)` for a column vector `d` of diagonal entries without
+//! building the diagonal matrix. The `_inplace` variants overwrite `self`.
+//! Either storage kind can be passed as the vector.
+
+use crate::matrix::*;
+use std::ops::Div;
+
+#[inline]
+fn broadcast<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    b: impl Fn(usize, usize) -> T,
+    op: impl Fn(T, T) -> T,
+) {
+    for (row, cells) in a.iter_mut().enumerate() {
+        for (col, cell) in cells.iter_mut().enumerate() {
+            *cell = op(*cell, b(row, col));
+        }
+    }
+}
+
+macro_rules! broadcast_methods {
+    ($row:ident, $row_inplace:ident, $col:ident, $col_inplace:ident, $f:expr, $what:literal) => {
+        #[doc = concat!("Returns a copy where each row is ", $what, " `row` element-wise.")]
+        #[inline]
+        pub fn $row<M: AsRef<[[T; COLS]; 1]>>(&self, row: &M) -> Self {
+            let mut c = self.clone();
+            c.$row_inplace(row);
+            c
+        }
+
+        #[doc = concat!("Replaces each row by the row ", $what, " `row` element-wise.")]
+        #[inline]
+        pub fn $row_inplace<M: AsRef<[[T; COLS]; 1]>>(&mut self, row: &M) {
+            let b = row.as_ref();
+            broadcast(self.array_mut(), |_, col| b[0][col], $f);
+        }
+
+        #[doc = concat!("Returns a copy where each column is ", $what, " `col` element-wise.")]
+        #[inline]
+        pub fn $col<M: AsRef<[[T; 1]; ROWS]>>(&self, col: &M) -> Self {
+            let mut c = self.clone();
+            c.$col_inplace(col);
+            c
+        }
+
+        #[doc = concat!("Replaces each column by the column ", $what, " `col` element-wise.")]
+        #[inline]
+        pub fn $col_inplace<M: AsRef<[[T; 1]; ROWS]>>(&mut self, col: &M) {
+            let b = col.as_ref();
+            broadcast(self.array_mut(), |row, _| b[row][0], $f);
+        }
+    };
+}
+
+macro_rules! impl_broadcast {
+    ($matrix:ident) => {
+        impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> $matrix<T, ROWS, COLS> {
+            broadcast_methods!(
+                add_row_broadcast,
+                add_row_broadcast_inplace,
+                add_col_broadcast,
+                add_col_broadcast_inplace,
+                |x, y| x + y,
+                "plus"
+            );
+            broadcast_methods!(
+                sub_row_broadcast,
+                sub_row_broadcast_inplace,
+                sub_col_broadcast,
+                sub_col_broadcast_inplace,
+                |x, y| x - y,
+                "minus"
+            );
+            broadcast_methods!(
+                mul_row_broadcast,
+                mul_row_broadcast_inplace,
+                mul_col_broadcast,
+                mul_col_broadcast_inplace,
+                |x, y| x * y,
+                "times"
+            );
+
+            /// Returns `diag(d) * self`, i.e., row `i` is scaled by `d[i]`.
+            #[inline]
+            pub fn scale_rows<M: AsRef<[[T; 1]; ROWS]>>(&self, d: &M) -> Self {
+                self.mul_col_broadcast(d)
+            }
+
+            /// Replaces `self` by `diag(d) * self`.
+            #[inline]
+            pub fn scale_rows_inplace<M: AsRef<[[T; 1]; ROWS]>>(&mut self, d: &M) {
+                self.mul_col_broadcast_inplace(d);
+            }
+
+            /// Returns `self * diag(d)`, i.e., column `j` is scaled by `d[j]`.
+            #[inline]
+            pub fn scale_cols<M: AsRef<[[T; 1]; COLS]>>(&self, d: &M) -> Self {
+                let mut c = self.clone();
+                c.scale_cols_inplace(d);
+                c
+            }
+
+            /// Replaces `self` by `self * diag(d)`.
+            #[inline]
+            pub fn scale_cols_inplace<M: AsRef<[[T; 1]; COLS]>>(&mut self, d: &M) {
+                let d = d.as_ref();
+                broadcast(self.array_mut(), |_, col| d[col][0], |x, y| x * y);
+            }
+        }
+
+        impl<T: Numeric<T> + Div<Output = T>, const ROWS: usize, const COLS: usize>
+            $matrix<T, ROWS, COLS>
+        {
+            broadcast_methods!(
+                div_row_broadcast,
+                div_row_broadcast_inplace,
+                div_col_broadcast,
+                div_col_broadcast_inplace,
+                |x, y| x / y,
+                "divided by"
+            );
+        }
+    };
+}
+
+impl_broadcast!(SMatrix);
+impl_broadcast!(HMatrix);
+
+#[cfg(test)]
+mod matrix_broadcast_tests {
+    #[test]
+    fn test_row_broadcast() {
+        let m = smatrix![1.0, 2.0; 3.0, 6.0];
+        let r = rvector![1.0, 2.0];
+        assert_eq!(m.add_row_broadcast(&r), smatrix![2.0, 4.0; 4.0, 8.0]);
+        assert_eq!(
+            m.sub_row_broadcast(&r.to_heap()),
+            smatrix![0.0, 0.0; 2.0, 4.0]
+        );
+        assert_eq!(m.mul_row_broadcast(&r), smatrix![1.0, 4.0; 3.0, 12.0]);
+        assert_eq!(m.div_row_broadcast(&r), smatrix![1.0, 1.0; 3.0, 3.0]);
+        let mut h = m.to_heap();
+        h.sub_row_broadcast_inplace(&r);
+        h.add_row_broadcast_inplace(&r);
+        h.mul_row_broadcast_inplace(&r);
+        h.div_row_broadcast_inplace(&r);
+        assert_eq!(h, m);
+    }
+
+    #[test]
+    fn test_col_broadcast() {
+        let m = hmatrix![1i32, 2; 3, 6];
+        let c = svector![1, 3];
+        assert_eq!(m.add_col_broadcast(&c), smatrix![2, 3; 6, 9]);
+        assert_eq!(m.sub_col_broadcast(&c), smatrix![0, 1; 0, 3]);
+        assert_eq!(m.mul_col_broadcast(&c.to_heap()), smatrix![1, 2; 9, 18]);
+        assert_eq!(m.div_col_broadcast(&c), smatrix![1, 2; 1, 2]);
+        let mut s = m.to_stack();
+        s.add_col_broadcast_inplace(&c);
+        s.sub_col_broadcast_inplace(&c);
+        s.mul_col_broadcast_inplace(&c);
+        s.div_col_broadcast_inplace(&c);
+        assert_eq!(s, m);
+    }
+
+    #[test]
+    fn test_centering() {
+        let data = smatrix![1.0, 10.0; 2.0, 20.0; 3.0, 30.0];
+        let means = rvector![2.0, 20.0];
+        let centered = data.sub_row_broadcast(&means);
+        assert_eq!(centered, smatrix![-1.0, -10.0; 0.0, 0.0; 1.0, 10.0]);
+    }
+
+    #[test]
+    fn test_scale() {
+        let m = smatrix![1, 2, 3; 4, 5, 6];
+        let d2 = svector![2, -1];
+        let d3 = svector![1, 0, 10];
+        let diag2 = smatrix![2, 0; 0, -1];
+        let diag3 = smatrix![1, 0, 0; 0, 0, 0; 0, 0, 10];
+        assert_eq!(m.scale_rows(&d2), diag2 * m);
+        assert_eq!(m.scale_cols(&d3), m * diag3);
+        let mut h = m.to_heap();
+        h.scale_rows_inplace(&d2);
+        h.scale_cols_inplace(&d3.to_heap());
+        assert_eq!(h, diag2 * m * diag3);
+    }
+}
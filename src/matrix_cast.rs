@@ -0,0 +1,337 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Element type conversions: cast, try_cast, checked_cast and as_cast
+//!
+//! `cast::<U>()` is lossless and requires `U: From<T>`, `try_cast::<U>()`
+//! requires `U: TryFrom<T>` and reports the position of the first element
+//! that doesn't fit, `checked_cast::<U>()` does the same for float elements
+//! (which have no `TryFrom` conversions), and `as_cast::<U>()` converts
+//! primitives with the semantics of the `as` operator (truncating, wrapping
+//! or saturating). The result has the same storage kind as the source matrix.
+
+use crate::matrix::*;
+use std::array::from_fn;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// An error which is returned by `try_cast` and `checked_cast` if an element
+/// can't be converted to the target element type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TryCastError {
+    /// The row of the first offending element
+    pub row: usize,
+    /// The column of the first offending element
+    pub col: usize,
+}
+
+impl Display for TryCastError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "element at row {}, column {} is out of range for the target type",
+            self.row, self.col
+        )
+    }
+}
+
+impl Error for TryCastError {}
+
+/// Conversion with the semantics of the `as` operator between the
+/// primitive element types.
+pub trait AsPrimitive<U>: Copy {
+    /// Converts `self` to `U` as `self as U` would.
+    fn as_(self) -> U;
+}
+
+macro_rules! impl_as_primitive {
+    ($($from:ty),*) => {
+        $(impl_as_primitive!(@from $from => i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);)*
+    };
+    (@from $from:ty => $($to:ty),*) => {
+        $(
+            impl AsPrimitive<$to> for $from {
+                #[inline]
+                fn as_(self) -> $to {
+                    self as $to
+                }
+            }
+        )*
+    };
+}
+
+impl_as_primitive!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64);
+
+/// Checked conversion from a float to a primitive element type.
+pub trait CheckedCast<U>: Copy {
+    /// Converts `self` to `U`, or returns `None` if `self` isn't finite,
+    /// is out of range for `U` or, for an integer `U`, isn't integral.
+    fn checked_cast(self) -> Option<U>;
+}
+
+macro_rules! impl_checked_cast {
+    (int: $($to:ty),*) => {
+        $(
+            impl_checked_cast!(@int f32 => $to);
+            impl_checked_cast!(@int f64 => $to);
+        )*
+    };
+    (@int $from:ty => $to:ty) => {
+        impl CheckedCast<$to> for $from {
+            #[inline]
+            fn checked_cast(self) -> Option<$to> {
+                // both bounds are powers of two (or zero) and thus exact
+                let lo = <$to>::MIN as f64;
+                let hi = 2.0 * ((<$to>::MAX / 2 + 1) as f64);
+                let x = self as f64;
+                if x.is_finite() && x.trunc() == x && lo <= x && x < hi {
+                    Some(x as $to)
+                } else {
+                    None
+                }
+            }
+        }
+    };
+    (float: $($from:ty => $to:ty),*) => {
+        $(
+            impl CheckedCast<$to> for $from {
+                #[inline]
+                fn checked_cast(self) -> Option<$to> {
+                    let x = self as $to;
+                    if self.is_finite() && x.is_finite() {
+                        Some(x)
+                    } else {
+                        None
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_cast!(int: i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_checked_cast!(float: f32 => f32, f32 => f64, f64 => f32, f64 => f64);
+
+#[inline]
+fn try_cast_row<T: Copy, U: TryFrom<T>, const COLS: usize>(
+    row: &[T; COLS],
+    row_idx: usize,
+) -> Result<[U; COLS], TryCastError> {
+    convert_row(row, row_idx, |x| U::try_from(x).ok())
+}
+
+#[inline]
+fn convert_row<T: Copy, U, const COLS: usize>(
+    row: &[T; COLS],
+    row_idx: usize,
+    f: impl Fn(T) -> Option<U>,
+) -> Result<[U; COLS], TryCastError> {
+    let cells = row.map(f);
+    match cells.iter().position(Option::is_none) {
+        Some(col) => Err(TryCastError { row: row_idx, col }),
+        None => Ok(cells.map(|cell| cell.unwrap())),
+    }
+}
+
+impl<T: Copy, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Converts every element losslessly to `U`.
+    ///
+    /// ```
+    /// use const_matrix::smatrix;
+    ///
+    /// let m = smatrix![1i32, -2; 3, 4].cast::<f64>();
+    /// assert_eq!(m, smatrix![1.0, -2.0; 3.0, 4.0]);
+    /// ```
+    #[inline]
+    pub fn cast<U: From<T>>(&self) -> SMatrix<U, ROWS, COLS> {
+        self.map(U::from)
+    }
+
+    /// Converts every element to `U` or returns the position of the
+    /// first element (in row-major order) that is out of range for `U`.
+    #[inline]
+    pub fn try_cast<U: TryFrom<T>>(&self) -> Result<SMatrix<U, ROWS, COLS>, TryCastError> {
+        let a = self.array();
+        let rows: [Result<[U; COLS], TryCastError>; ROWS] =
+            from_fn(|row| try_cast_row(&a[row], row));
+        if let Some(Err(e)) = rows.iter().find(|row| row.is_err()) {
+            return Err(*e);
+        }
+        Ok(SMatrix::new(rows.map(|row| row.unwrap())))
+    }
+
+    /// Converts every float element to `U` or returns the position of the
+    /// first element (in row-major order) that isn't finite, is out of
+    /// range for `U` or, for an integer `U`, isn't integral.
+    ///
+    /// ```
+    /// use const_matrix::{matrix::TryCastError, smatrix};
+    ///
+    /// assert_eq!(smatrix![1.0, -2.0].checked_cast::<i8>(), Ok(smatrix![1i8, -2]));
+    /// let err = smatrix![1.0, 2.5].checked_cast::<i8>();
+    /// assert_eq!(err, Err(TryCastError { row: 0, col: 1 }));
+    /// ```
+    #[inline]
+    pub fn checked_cast<U>(&self) -> Result<SMatrix<U, ROWS, COLS>, TryCastError>
+    where
+        T: CheckedCast<U>,
+    {
+        let a = self.array();
+        let rows: [Result<[U; COLS], TryCastError>; ROWS] =
+            from_fn(|row| convert_row(&a[row], row, T::checked_cast));
+        if let Some(Err(e)) = rows.iter().find(|row| row.is_err()) {
+            return Err(*e);
+        }
+        Ok(SMatrix::new(rows.map(|row| row.unwrap())))
+    }
+
+    /// Converts every element to `U` as the `as` operator would.
+    #[inline]
+    pub fn as_cast<U>(&self) -> SMatrix<U, ROWS, COLS>
+    where
+        T: AsPrimitive<U>,
+    {
+        self.map(T::as_)
+    }
+}
+
+impl<T: Copy, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Converts every element losslessly to `U`.
+    #[inline]
+    pub fn cast<U: From<T>>(&self) -> HMatrix<U, ROWS, COLS> {
+        self.map(U::from)
+    }
+
+    /// Converts every element to `U` or returns the position of the
+    /// first element (in row-major order) that is out of range for `U`.
+    #[inline]
+    pub fn try_cast<U: TryFrom<T>>(&self) -> Result<HMatrix<U, ROWS, COLS>, TryCastError> {
+        let rows = self
+            .array()
+            .iter()
+            .enumerate()
+            .map(|(row_idx, row)| try_cast_row(row, row_idx))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HMatrix::from_rows(rows.into_iter()))
+    }
+
+    /// Converts every float element to `U` or returns the position of the
+    /// first element (in row-major order) that isn't finite, is out of
+    /// range for `U` or, for an integer `U`, isn't integral.
+    #[inline]
+    pub fn checked_cast<U>(&self) -> Result<HMatrix<U, ROWS, COLS>, TryCastError>
+    where
+        T: CheckedCast<U>,
+    {
+        let rows = self
+            .array()
+            .iter()
+            .enumerate()
+            .map(|(row_idx, row)| convert_row(row, row_idx, T::checked_cast))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(HMatrix::from_rows(rows.into_iter()))
+    }
+
+    /// Converts every element to `U` as the `as` operator would.
+    #[inline]
+    pub fn as_cast<U>(&self) -> HMatrix<U, ROWS, COLS>
+    where
+        T: AsPrimitive<U>,
+    {
+        self.map(T::as_)
+    }
+}
+
+#[cfg(test)]
+mod matrix_cast_tests {
+    use super::*;
+
+    #[test]
+    fn test_cast() {
+        let m = smatrix![1i8, -2; 3, i8::MIN];
+        assert_eq!(m.cast::<i32>(), smatrix![1, -2; 3, -128]);
+        assert_eq!(m.cast::<f32>(), smatrix![1.0f32, -2.0; 3.0, -128.0]);
+        let h: HMatrix<i128, 2, 2> = m.to_heap().cast::<i64>().cast();
+        assert_eq!(h, smatrix![1i128, -2; 3, -128]);
+        let f = smatrix![0.5f32, -1.25].to_heap().cast::<f64>();
+        assert_eq!(f, smatrix![0.5f64, -1.25]);
+    }
+
+    #[test]
+    fn test_try_cast() {
+        let m = smatrix![1i32, 200; -129, 4];
+        assert_eq!(m.try_cast::<i16>(), Ok(smatrix![1i16, 200; -129, 4]));
+        assert_eq!(m.try_cast::<i8>(), Err(TryCastError { row: 0, col: 1 }));
+        let h = hmatrix![1i64, 2; -129, 4];
+        assert_eq!(h.try_cast::<i8>(), Err(TryCastError { row: 1, col: 0 }));
+        assert_eq!(h.try_cast::<i16>(), Ok(hmatrix![1i16, 2; -129, 4]));
+        assert_eq!(
+            TryCastError { row: 1, col: 0 }.to_string(),
+            "element at row 1, column 0 is out of range for the target type"
+        );
+    }
+
+    #[test]
+    fn test_checked_cast() {
+        let m = smatrix![1.0f64, -128.0; 127.0, 0.0];
+        assert_eq!(m.checked_cast::<i8>(), Ok(smatrix![1i8, -128; 127, 0]));
+        assert_eq!(m.checked_cast::<u8>(), Err(TryCastError { row: 0, col: 1 }));
+        assert_eq!(
+            m.to_heap().checked_cast::<f32>(),
+            Ok(hmatrix![1.0f32, -128.0; 127.0, 0.0])
+        );
+        // out of range, fractional and non-finite elements
+        let h = hmatrix![255.0f32, 256.0; 0.5, f32::NAN];
+        assert_eq!(h.checked_cast::<u8>(), Err(TryCastError { row: 0, col: 1 }));
+        assert_eq!(
+            h.checked_cast::<u16>(),
+            Err(TryCastError { row: 1, col: 0 })
+        );
+        assert_eq!(
+            h.checked_cast::<f64>(),
+            Err(TryCastError { row: 1, col: 1 })
+        );
+        assert_eq!(smatrix![-0.0f32].checked_cast::<u32>(), Ok(smatrix![0u32]));
+        assert_eq!(
+            smatrix![-1.0f32].checked_cast::<u32>(),
+            Err(TryCastError { row: 0, col: 0 })
+        );
+        assert!(smatrix![f64::INFINITY].checked_cast::<f64>().is_err());
+        assert!(smatrix![1e300f64].checked_cast::<f32>().is_err());
+        // the bounds of the widest types
+        let wide = smatrix![-2f64.powi(127), 1.7e38];
+        assert_eq!(
+            wide.checked_cast::<i128>(),
+            Ok(smatrix![i128::MIN, 1.7e38f64 as i128])
+        );
+        assert!(smatrix![2f64.powi(127)].checked_cast::<i128>().is_err());
+        assert_eq!(
+            smatrix![3.4e38f32].checked_cast::<u128>(),
+            Ok(smatrix![3.4e38f32 as u128])
+        );
+        assert!(smatrix![u64::MAX as f64].checked_cast::<u64>().is_err());
+        assert_eq!(
+            smatrix![(1u64 << 63) as f64].checked_cast::<u64>(),
+            Ok(smatrix![1u64 << 63])
+        );
+    }
+
+    #[test]
+    fn test_as_cast() {
+        let f = smatrix![1.9f64, -1.9; 300.0, f64::NAN];
+        assert_eq!(f.as_cast::<i32>(), smatrix![1, -1; 300, 0]);
+        // float to int saturates
+        assert_eq!(f.as_cast::<i8>(), smatrix![1i8, -1; 127, 0]);
+        // int to int wraps
+        let i = hmatrix![300i32, -129];
+        assert_eq!(i.as_cast::<i8>(), hmatrix![44i8, 127]);
+        let down = hmatrix![0.1f64, 1e300].as_cast::<f32>();
+        assert_eq!(down, hmatrix![0.1f32, f32::INFINITY]);
+        assert_eq!(smatrix![7isize].as_cast::<i128>(), smatrix![7i128]);
+    }
+}
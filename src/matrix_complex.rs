@@ -0,0 +1,175 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Complex matrices: conjugate, adjoint (conjugate transpose) and the
+//! Hermitian and unitary checks
+//!
+//! The checks compare element-wise with an absolute tolerance `eps` on
+//! the modulus of the difference.
+
+use crate::complex::Complex;
+use crate::matrix::*;
+use crate::matrix_mul::multiply;
+use std::ops::Neg;
+
+/// `false` if any part is NaN
+#[inline]
+fn is_close<T: Real>(a: Complex<T>, b: Complex<T>, eps: T) -> bool {
+    (a - b).abs() <= eps
+}
+
+#[inline]
+fn is_hermitian<T: Real, const N: usize>(a: &[[Complex<T>; N]; N], eps: T) -> bool {
+    a.iter().enumerate().all(|(i, row)| {
+        row.iter()
+            .enumerate()
+            .skip(i)
+            .all(|(j, cell)| is_close(*cell, a[j][i].conj(), eps))
+    })
+}
+
+/// `a` is the product `Aᴴ A`
+#[inline]
+fn is_identity<T: Real, const N: usize>(a: &[[Complex<T>; N]; N], eps: T) -> bool {
+    for (i, row) in a.iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            let expected = if i == j {
+                Complex::one()
+            } else {
+                Complex::default()
+            };
+            if !is_close(*cell, expected, eps) {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize>
+    SMatrix<Complex<T>, ROWS, COLS>
+{
+    /// Returns the element-wise complex conjugate.
+    #[inline]
+    pub fn conj(&self) -> SMatrix<Complex<T>, ROWS, COLS> {
+        self.map(|z| z.conj())
+    }
+
+    /// Returns the adjoint (conjugate transpose) `Aᴴ`.
+    #[inline]
+    pub fn adjoint(&self) -> SMatrix<Complex<T>, COLS, ROWS> {
+        self.trans().conj()
+    }
+}
+
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize>
+    HMatrix<Complex<T>, ROWS, COLS>
+{
+    /// Returns the element-wise complex conjugate.
+    #[inline]
+    pub fn conj(&self) -> HMatrix<Complex<T>, ROWS, COLS> {
+        self.map(|z| z.conj())
+    }
+
+    /// Returns the adjoint (conjugate transpose) `Aᴴ`.
+    #[inline]
+    pub fn adjoint(&self) -> HMatrix<Complex<T>, COLS, ROWS> {
+        let mut adjoint = self.trans();
+        adjoint.map_inplace(|z| z.conj());
+        adjoint
+    }
+}
+
+impl<T: Real, const N: usize> SMatrix<Complex<T>, N, N> {
+    /// Returns `true` if this matrix equals its adjoint up to `eps`.
+    #[inline]
+    pub fn is_hermitian(&self, eps: T) -> bool {
+        is_hermitian(self.array(), eps)
+    }
+
+    /// Returns `true` if `Aᴴ A` equals the identity up to `eps`.
+    #[inline]
+    pub fn is_unitary(&self, eps: T) -> bool {
+        let mut product = MF::<Complex<T>, N, N>::new_stack();
+        multiply(self.adjoint().array(), self.array(), product.array_mut());
+        is_identity(product.array(), eps)
+    }
+}
+
+impl<T: Real, const N: usize> HMatrix<Complex<T>, N, N> {
+    /// Returns `true` if this matrix equals its adjoint up to `eps`.
+    #[inline]
+    pub fn is_hermitian(&self, eps: T) -> bool {
+        is_hermitian(self.array(), eps)
+    }
+
+    /// Returns `true` if `Aᴴ A` equals the identity up to `eps`.
+    #[inline]
+    pub fn is_unitary(&self, eps: T) -> bool {
+        let mut product = MF::<Complex<T>, N, N>::new_heap();
+        multiply(self.adjoint().array(), self.array(), product.array_mut());
+        is_identity(product.array(), eps)
+    }
+}
+
+#[cfg(test)]
+mod matrix_complex_tests {
+    use super::*;
+
+    fn c(re: f64, im: f64) -> Complex<f64> {
+        Complex::new(re, im)
+    }
+
+    #[test]
+    fn test_conj_adjoint() {
+        let a = smatrix![c(1.0, 2.0), c(3.0, -1.0), c(0.0, 1.0)];
+        assert_eq!(a.conj(), smatrix![c(1.0, -2.0), c(3.0, 1.0), c(0.0, -1.0)]);
+        assert_eq!(
+            a.adjoint(),
+            svector![c(1.0, -2.0), c(3.0, 1.0), c(0.0, -1.0)]
+        );
+        assert_eq!(a.to_heap().adjoint(), a.adjoint());
+        assert_eq!(a.to_heap().conj(), a.conj());
+        assert_eq!(a.adjoint().adjoint(), a);
+        // (AB)ᴴ = BᴴAᴴ
+        let b = smatrix![c(1.0, 1.0); c(2.0, 0.0); c(0.0, -3.0)];
+        assert_eq!((a * b).adjoint(), b.adjoint() * a.adjoint());
+    }
+
+    #[test]
+    fn test_is_hermitian() {
+        let h = smatrix![c(2.0, 0.0), c(1.0, -1.0); c(1.0, 1.0), c(3.0, 0.0)];
+        assert!(h.is_hermitian(0.0));
+        assert!(h.to_heap().is_hermitian(0.0));
+        let not_real_diagonal = smatrix![c(2.0, 1e-3), c(1.0, -1.0); c(1.0, 1.0), c(3.0, 0.0)];
+        assert!(!not_real_diagonal.is_hermitian(1e-6));
+        assert!(not_real_diagonal.is_hermitian(1e-2));
+        let symmetric = smatrix![c(0.0, 0.0), c(0.0, 1.0); c(0.0, 1.0), c(0.0, 0.0)];
+        assert!(!symmetric.is_hermitian(1e-12));
+        assert!(!smatrix![c(f64::NAN, 0.0)].is_hermitian(1.0));
+    }
+
+    #[test]
+    fn test_is_unitary() {
+        let s = std::f64::consts::FRAC_1_SQRT_2;
+        let u = smatrix![c(s, 0.0), c(0.0, s); c(0.0, s), c(s, 0.0)];
+        assert!(u.is_unitary(1e-15));
+        assert!(u.to_heap().is_unitary(1e-15));
+        assert!(!(Complex::new(2.0, 0.0) * u).is_unitary(1e-6));
+        assert!(!u.is_hermitian(1e-6));
+        // the 8 x 8 discrete Fourier transform
+        let mut dft = MF::<Complex<f64>, 8, 8>::new_heap();
+        for j in 0..8 {
+            for k in 0..8 {
+                let theta = -2.0 * std::f64::consts::PI * (j * k) as f64 / 8.0;
+                dft[j][k] = Complex::from_polar(1.0 / 8.0f64.sqrt(), theta);
+            }
+        }
+        assert!(dft.is_unitary(1e-14));
+        assert!(dft.to_stack().is_unitary(1e-14));
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `const fn` construction and arithmetic for stack matrices of primitive types
+
+use crate::matrix::*;
+
+// The generic constructors and operators can't be `const fn` since neither
+// `Default::default()` nor the operator traits can be called in a `const`
+// context. Therefore we have to provide dedicated `const fn` variants for
+// each primitive type we want to support.
+macro_rules! impl_const_fn {
+    ($t:ty, $zero:expr, $one:expr) => {
+        impl<const ROWS: usize, const COLS: usize> MF<$t, ROWS, COLS> {
+            /// Create a new matrix initialized with zeros on the stack
+            /// (usable in `const` contexts).
+            #[inline]
+            pub const fn const_new_stack() -> SMatrix<$t, ROWS, COLS> {
+                SMatrix::new([[$zero; COLS]; ROWS])
+            }
+
+            /// Create an identity matrix on the stack (usable in `const` contexts).
+            #[inline]
+            pub const fn const_unit_stack() -> SMatrix<$t, ROWS, ROWS> {
+                MF::<$t, ROWS, ROWS>::const_diag_stack($one)
+            }
+
+            /// Create a diagonal matrix with initial diagonal value `diag_val`
+            /// on the stack (usable in `const` contexts).
+            #[inline]
+            pub const fn const_diag_stack(diag_val: $t) -> SMatrix<$t, ROWS, ROWS> {
+                let mut c = [[$zero; ROWS]; ROWS];
+                let mut i = 0;
+                while i < ROWS {
+                    c[i][i] = diag_val;
+                    i += 1;
+                }
+                SMatrix::new(c)
+            }
+        }
+
+        impl<const ROWS: usize, const COLS: usize> SMatrix<$t, ROWS, COLS> {
+            /// Creates a stack-allocated transpose of this matrix
+            /// (usable in `const` contexts).
+            #[inline]
+            pub const fn const_trans(&self) -> SMatrix<$t, COLS, ROWS> {
+                let a = self.array();
+                let mut c = [[$zero; ROWS]; COLS];
+                let mut row = 0;
+                while row < ROWS {
+                    let mut col = 0;
+                    while col < COLS {
+                        c[col][row] = a[row][col];
+                        col += 1;
+                    }
+                    row += 1;
+                }
+                SMatrix::new(c)
+            }
+
+            /// Adds `rhs` to this matrix (usable in `const` contexts).
+            #[inline]
+            pub const fn const_add(
+                &self,
+                rhs: &SMatrix<$t, ROWS, COLS>,
+            ) -> SMatrix<$t, ROWS, COLS> {
+                let (a, b) = (self.array(), rhs.array());
+                let mut c = [[$zero; COLS]; ROWS];
+                let mut row = 0;
+                while row < ROWS {
+                    let mut col = 0;
+                    while col < COLS {
+                        c[row][col] = a[row][col] + b[row][col];
+                        col += 1;
+                    }
+                    row += 1;
+                }
+                SMatrix::new(c)
+            }
+
+            /// Subtracts `rhs` from this matrix (usable in `const` contexts).
+            #[inline]
+            pub const fn const_sub(
+                &self,
+                rhs: &SMatrix<$t, ROWS, COLS>,
+            ) -> SMatrix<$t, ROWS, COLS> {
+                let (a, b) = (self.array(), rhs.array());
+                let mut c = [[$zero; COLS]; ROWS];
+                let mut row = 0;
+                while row < ROWS {
+                    let mut col = 0;
+                    while col < COLS {
+                        c[row][col] = a[row][col] - b[row][col];
+                        col += 1;
+                    }
+                    row += 1;
+                }
+                SMatrix::new(c)
+            }
+
+            /// Multiplies this matrix with `rhs` (usable in `const` contexts).
+            #[inline]
+            pub const fn const_mul<const COLS_RIGHT: usize>(
+                &self,
+                rhs: &SMatrix<$t, COLS, COLS_RIGHT>,
+            ) -> SMatrix<$t, ROWS, COLS_RIGHT> {
+                let (a, b) = (self.array(), rhs.array());
+                let mut c = [[$zero; COLS_RIGHT]; ROWS];
+                let mut row_a = 0;
+                while row_a < ROWS {
+                    let mut col_b = 0;
+                    while col_b < COLS_RIGHT {
+                        let mut col_a = 0;
+                        while col_a < COLS {
+                            c[row_a][col_b] += a[row_a][col_a] * b[col_a][col_b];
+                            col_a += 1;
+                        }
+                        col_b += 1;
+                    }
+                    row_a += 1;
+                }
+                SMatrix::new(c)
+            }
+
+            /// Multiplies this matrix with the scalar `scalar`
+            /// (usable in `const` contexts).
+            #[inline]
+            pub const fn const_mul_scalar(&self, scalar: $t) -> SMatrix<$t, ROWS, COLS> {
+                let a = self.array();
+                let mut c = [[$zero; COLS]; ROWS];
+                let mut row = 0;
+                while row < ROWS {
+                    let mut col = 0;
+                    while col < COLS {
+                        c[row][col] = scalar * a[row][col];
+                        col += 1;
+                    }
+                    row += 1;
+                }
+                SMatrix::new(c)
+            }
+        }
+    };
+}
+
+impl_const_fn!(i8, 0, 1);
+impl_const_fn!(i16, 0, 1);
+impl_const_fn!(i32, 0, 1);
+impl_const_fn!(i64, 0, 1);
+impl_const_fn!(i128, 0, 1);
+impl_const_fn!(isize, 0, 1);
+impl_const_fn!(u8, 0, 1);
+impl_const_fn!(u16, 0, 1);
+impl_const_fn!(u32, 0, 1);
+impl_const_fn!(u64, 0, 1);
+impl_const_fn!(u128, 0, 1);
+impl_const_fn!(usize, 0, 1);
+impl_const_fn!(f32, 0.0, 1.0);
+impl_const_fn!(f64, 0.0, 1.0);
+
+#[cfg(test)]
+mod matrix_const_tests {
+    use super::*;
+
+    const C: f64 = 0.5;
+    const S: f64 = 0.866_025_403_784_438_6;
+    const ROT_Z: SMatrix<f64, 3, 3> = SMatrix::new([[C, -S, 0.0], [S, C, 0.0], [0.0, 0.0, 1.0]]);
+    const ROT_Z_INV: SMatrix<f64, 3, 3> = ROT_Z.const_trans();
+    const PRODUCT: SMatrix<f64, 3, 3> = ROT_Z.const_mul(&ROT_Z_INV);
+    const UNIT: SMatrix<f64, 3, 3> = MF::<f64, 3, 3>::const_unit_stack();
+
+    #[test]
+    fn test_const_rotation() {
+        let diff = PRODUCT.const_sub(&UNIT);
+        for row in 0..3 {
+            for col in 0..3 {
+                assert!(diff[row][col].abs() < 1e-15);
+            }
+        }
+        assert_eq!(ROT_Z_INV[0][1], S);
+        assert_eq!(ROT_Z_INV[1][0], -S);
+    }
+
+    #[test]
+    fn test_const_int() {
+        const A: SMatrix<i32, 2, 3> = SMatrix::new([[1, 2, 3], [4, 5, 6]]);
+        const B: SMatrix<i32, 3, 2> = A.const_trans();
+        const C: SMatrix<i32, 2, 2> = A.const_mul(&B);
+        const D: SMatrix<i32, 2, 2> = C.const_add(&MF::<i32, 2, 2>::const_diag_stack(10));
+        const E: SMatrix<i32, 2, 2> = D.const_mul_scalar(2);
+        const Z: SMatrix<i32, 2, 2> = MF::<i32, 2, 2>::const_new_stack();
+        assert_eq!(C[0][0], 14);
+        assert_eq!(C[0][1], 32);
+        assert_eq!(C[1][0], 32);
+        assert_eq!(C[1][1], 77);
+        assert_eq!(E[0][0], 48);
+        assert_eq!(E[1][1], 174);
+        assert_eq!(Z[1][1], 0);
+        let runtime = MF::<i32, 2, 3>::new_stack();
+        assert_eq!(runtime.const_trans()[2][1], 0);
+    }
+
+    #[test]
+    fn test_const_unsigned() {
+        const A: SMatrix<u8, 2, 2> = SMatrix::new([[1, 2], [3, 4]]);
+        const B: SMatrix<u8, 2, 2> = A.const_mul(&A.const_trans()).const_sub(&A);
+        const I: SMatrix<usize, 3, 3> = MF::<usize, 3, 3>::const_unit_stack();
+        const J: SMatrix<u128, 2, 2> = MF::<u128, 2, 2>::const_diag_stack(3).const_mul_scalar(2);
+        assert_eq!(B, smatrix![4u8, 9; 8, 21]);
+        assert_eq!(I.const_add(&I), smatrix![2usize, 0, 0; 0, 2, 0; 0, 0, 2]);
+        assert_eq!(J, smatrix![6u128, 0; 0, 6]);
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Display, LowerExp and UpperExp implementations
+//!
+//! The elements are printed in aligned columns, one matrix row per line.
+//! The alternate form (`{:#}`) additionally encloses the rows in brackets.
+//! Precision (`{:.3}`) is applied to each element and width (`{:8}`) is
+//! used as the minimum column width. As in NumPy, the middle of matrices
+//! with more than 1000 elements is elided and replaced by `⋯` and `⋮`.
+//! Elements that print on more than one line (e.g., the `SMatrix` blocks
+//! of a block matrix) are laid out as blocks.
+
+use crate::matrix::*;
+use std::fmt::{Display, Formatter, LowerExp, Result, UpperExp};
+use std::iter::once;
+
+// same as NumPy's defaults
+const SUMMARY_THRESHOLD: usize = 1000;
+const EDGE_ITEMS: usize = 3;
+
+/// The indices of the rows (or columns) that get printed where
+/// `None` stands for the elided part in the middle.
+fn shown(n: usize, summarize: bool) -> Vec<Option<usize>> {
+    if summarize && n > 2 * EDGE_ITEMS {
+        (0..EDGE_ITEMS)
+            .map(Some)
+            .chain(once(None))
+            .chain((n - EDGE_ITEMS..n).map(Some))
+            .collect()
+    } else {
+        (0..n).map(Some).collect()
+    }
+}
+
+fn fmt_matrix<T, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    f: &mut Formatter<'_>,
+    fmt_cell: fn(&T, Option<usize>, bool) -> String,
+) -> Result {
+    let summarize = ROWS * COLS > SUMMARY_THRESHOLD;
+    let (precision, alternate) = (f.precision(), f.alternate());
+    let rows = shown(ROWS, summarize);
+    let cols = shown(COLS, summarize);
+    let cells: Vec<Vec<Vec<String>>> = rows
+        .iter()
+        .map(|row| {
+            cols.iter()
+                .map(|col| match (row, col) {
+                    (Some(row), Some(col)) => fmt_cell(&a[*row][*col], precision, alternate)
+                        .lines()
+                        .map(String::from)
+                        .collect(),
+                    (Some(_), None) => vec!["⋯".to_string()],
+                    (None, Some(_)) => vec!["⋮".to_string()],
+                    (None, None) => vec!["⋱".to_string()],
+                })
+                .collect()
+        })
+        .collect();
+    let min_width = f.width().unwrap_or(0);
+    let widths: Vec<usize> = (0..cols.len())
+        .map(|col| {
+            cells
+                .iter()
+                .flat_map(|row| row[col].iter())
+                .map(|line| line.chars().count())
+                .fold(min_width, usize::max)
+        })
+        .collect();
+    let blocks = cells.iter().flatten().any(|lines| lines.len() > 1);
+
+    if cells.is_empty() && alternate {
+        return f.write_str("[]");
+    }
+    for (i, row) in cells.iter().enumerate() {
+        if i > 0 {
+            writeln!(f)?;
+            if blocks && !alternate {
+                writeln!(f)?;
+            }
+        }
+        let height = row.iter().map(Vec::len).fold(1, usize::max);
+        for k in 0..height {
+            if k > 0 {
+                writeln!(f)?;
+            }
+            if alternate {
+                f.write_str(match (i, k) {
+                    (0, 0) => "[[",
+                    (_, 0) => " [",
+                    _ => "  ",
+                })?;
+            }
+            for (j, lines) in row.iter().enumerate() {
+                if j > 0 {
+                    f.write_str(if alternate && !blocks && k == 0 {
+                        ", "
+                    } else {
+                        "  "
+                    })?;
+                }
+                let line = lines.get(k).map(String::as_str).unwrap_or("");
+                write!(f, "{:>width$}", line, width = widths[j])?;
+            }
+            if alternate && k + 1 == height {
+                f.write_str(if i + 1 == cells.len() { "]]" } else { "]," })?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn display_cell<T: Display>(cell: &T, precision: Option<usize>, alternate: bool) -> String {
+    match (precision, alternate) {
+        (Some(p), false) => format!("{:.*}", p, cell),
+        (Some(p), true) => format!("{:#.*}", p, cell),
+        (None, false) => format!("{}", cell),
+        (None, true) => format!("{:#}", cell),
+    }
+}
+
+fn lower_exp_cell<T: LowerExp>(cell: &T, precision: Option<usize>, alternate: bool) -> String {
+    match (precision, alternate) {
+        (Some(p), false) => format!("{:.*e}", p, cell),
+        (Some(p), true) => format!("{:#.*e}", p, cell),
+        (None, false) => format!("{:e}", cell),
+        (None, true) => format!("{:#e}", cell),
+    }
+}
+
+fn upper_exp_cell<T: UpperExp>(cell: &T, precision: Option<usize>, alternate: bool) -> String {
+    match (precision, alternate) {
+        (Some(p), false) => format!("{:.*E}", p, cell),
+        (Some(p), true) => format!("{:#.*E}", p, cell),
+        (None, false) => format!("{:E}", cell),
+        (None, true) => format!("{:#E}", cell),
+    }
+}
+
+impl<T: Display, const ROWS: usize, const COLS: usize> Display for SMatrix<T, ROWS, COLS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        fmt_matrix(self.array(), f, display_cell)
+    }
+}
+
+impl<T: Display, const ROWS: usize, const COLS: usize> Display for HMatrix<T, ROWS, COLS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        fmt_matrix(self.array(), f, display_cell)
+    }
+}
+
+impl<T: LowerExp, const ROWS: usize, const COLS: usize> LowerExp for SMatrix<T, ROWS, COLS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        fmt_matrix(self.array(), f, lower_exp_cell)
+    }
+}
+
+impl<T: LowerExp, const ROWS: usize, const COLS: usize> LowerExp for HMatrix<T, ROWS, COLS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        fmt_matrix(self.array(), f, lower_exp_cell)
+    }
+}
+
+impl<T: UpperExp, const ROWS: usize, const COLS: usize> UpperExp for SMatrix<T, ROWS, COLS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        fmt_matrix(self.array(), f, upper_exp_cell)
+    }
+}
+
+impl<T: UpperExp, const ROWS: usize, const COLS: usize> UpperExp for HMatrix<T, ROWS, COLS> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        fmt_matrix(self.array(), f, upper_exp_cell)
+    }
+}
+
+#[cfg(test)]
+mod matrix_display_tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let a = SMatrix::new([[1, -20, 3], [400, 5, 6]]);
+        assert_eq!(format!("{}", a), "  1  -20  3\n400    5  6");
+        assert_eq!(format!("{}", a.to_heap()), format!("{}", a));
+        assert_eq!(format!("{:#}", a), "[[  1, -20, 3],\n [400,   5, 6]]");
+        assert_eq!(format!("{:4}", SMatrix::new([[1, 2]])), "   1     2");
+    }
+
+    #[test]
+    fn test_precision_and_exp() {
+        let a = HMatrix::new([[1.0f64, 2.5], [-0.125, 100.0]]);
+        assert_eq!(format!("{:.2}", a), " 1.00    2.50\n-0.12  100.00");
+        assert_eq!(format!("{:e}", a), "     1e0  2.5e0\n-1.25e-1    1e2");
+        assert_eq!(format!("{:.1E}", a), "  1.0E0  2.5E0\n-1.2E-1  1.0E2");
+    }
+
+    #[test]
+    fn test_elision() {
+        let mut a = MF::<i32, 40, 30>::new_heap();
+        for row in 0..40 {
+            for col in 0..30 {
+                a[row][col] = (row * 100 + col) as i32;
+            }
+        }
+        let s = format!("{}", a);
+        let lines: Vec<&str> = s.lines().collect();
+        assert_eq!(lines.len(), 7);
+        assert_eq!(lines[0], "   0     1     2  ⋯    27    28    29");
+        assert_eq!(lines[3], "   ⋮     ⋮     ⋮  ⋱     ⋮     ⋮     ⋮");
+        assert_eq!(lines[6], "3900  3901  3902  ⋯  3927  3928  3929");
+        // small enough to be printed completely
+        let b = MF::<i32, 10, 10>::new_stack();
+        assert_eq!(format!("{}", b).lines().count(), 10);
+    }
+
+    #[test]
+    fn test_block_matrix() {
+        let unit = MF::<i32, 2, 2>::unit_stack();
+        let mut blocks = MF::<SMatrix<i32, 2, 2>, 2, 2>::new_stack();
+        blocks[0][0] = unit;
+        blocks[1][1] = unit;
+        assert_eq!(
+            format!("{}", blocks),
+            "1  0  0  0\n0  1  0  0\n\n0  0  1  0\n0  0  0  1"
+        );
+        assert_eq!(
+            format!("{:#}", blocks),
+            "[[[[1, 0],  [[0, 0],\n   [0, 1]]   [0, 0]]],\n [[[0, 0],  [[1, 0],\n   [0, 0]]   [0, 1]]]]"
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let a = MF::<f64, 0, 3>::new_stack();
+        assert_eq!(format!("{}", a), "");
+        assert_eq!(format!("{:#}", a), "[]");
+    }
+}
@@ -0,0 +1,373 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! PartialEq, Eq and Hash implementations and approximate equality for floats
+
+use crate::matrix::*;
+use std::hash::{Hash, Hasher};
+
+// A) SMatrix == SMatrix
+impl<T: PartialEq, const ROWS: usize, const COLS: usize> PartialEq<SMatrix<T, ROWS, COLS>>
+    for SMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn eq(&self, other: &SMatrix<T, ROWS, COLS>) -> bool {
+        self.array() == other.array()
+    }
+}
+
+// B) SMatrix == HMatrix
+impl<T: PartialEq, const ROWS: usize, const COLS: usize> PartialEq<HMatrix<T, ROWS, COLS>>
+    for SMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn eq(&self, other: &HMatrix<T, ROWS, COLS>) -> bool {
+        self.array() == other.array()
+    }
+}
+
+// C) HMatrix == HMatrix
+impl<T: PartialEq, const ROWS: usize, const COLS: usize> PartialEq<HMatrix<T, ROWS, COLS>>
+    for HMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn eq(&self, other: &HMatrix<T, ROWS, COLS>) -> bool {
+        self.array() == other.array()
+    }
+}
+
+// D) HMatrix == SMatrix
+impl<T: PartialEq, const ROWS: usize, const COLS: usize> PartialEq<SMatrix<T, ROWS, COLS>>
+    for HMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn eq(&self, other: &SMatrix<T, ROWS, COLS>) -> bool {
+        self.array() == other.array()
+    }
+}
+
+impl<T: Eq, const ROWS: usize, const COLS: usize> Eq for SMatrix<T, ROWS, COLS> {}
+
+impl<T: Eq, const ROWS: usize, const COLS: usize> Eq for HMatrix<T, ROWS, COLS> {}
+
+// SMatrix and HMatrix must hash identically since they can compare equal
+impl<T: Hash, const ROWS: usize, const COLS: usize> Hash for SMatrix<T, ROWS, COLS> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.array().hash(state);
+    }
+}
+
+impl<T: Hash, const ROWS: usize, const COLS: usize> Hash for HMatrix<T, ROWS, COLS> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.array().hash(state);
+    }
+}
+
+macro_rules! impl_approx_eq {
+    ($t:ty, $bits:ty) => {
+        impl ApproxEq for $t {
+            #[inline]
+            fn abs_diff_eq(self, other: $t, epsilon: $t) -> bool {
+                (self - other).abs() <= epsilon
+            }
+
+            #[inline]
+            fn relative_eq(self, other: $t, epsilon: $t, max_relative: $t) -> bool {
+                if self == other {
+                    return true;
+                }
+                if self.is_infinite() || other.is_infinite() {
+                    return false;
+                }
+                let diff = (self - other).abs();
+                if diff <= epsilon {
+                    return true;
+                }
+                diff <= self.abs().max(other.abs()) * max_relative
+            }
+
+            #[inline]
+            fn ulps_eq(self, other: $t, epsilon: $t, max_ulps: u32) -> bool {
+                if (self - other).abs() <= epsilon {
+                    return true;
+                }
+                if self.is_sign_positive() != other.is_sign_positive()
+                    || self.is_nan()
+                    || other.is_nan()
+                {
+                    return false;
+                }
+                let (a, b) = (self.to_bits() as $bits, other.to_bits() as $bits);
+                a.wrapping_sub(b).unsigned_abs() <= max_ulps.into()
+            }
+        }
+    };
+}
+
+// Scalar comparisons used by the element-wise matrix comparisons below
+trait ApproxEq: Copy {
+    fn abs_diff_eq(self, other: Self, epsilon: Self) -> bool;
+    fn relative_eq(self, other: Self, epsilon: Self, max_relative: Self) -> bool;
+    fn ulps_eq(self, other: Self, epsilon: Self, max_ulps: u32) -> bool;
+}
+
+impl_approx_eq!(f32, i32);
+impl_approx_eq!(f64, i64);
+
+#[inline]
+fn all_cells<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    b: &[[T; COLS]; ROWS],
+    cell_eq: impl Fn(T, T) -> bool,
+) -> bool {
+    a.iter()
+        .zip(b.iter())
+        .all(|(row_a, row_b)| row_a.iter().zip(row_b.iter()).all(|(x, y)| cell_eq(*x, *y)))
+}
+
+macro_rules! impl_matrix_approx_eq {
+    ($t:ty, $matrix:ident) => {
+        impl<const ROWS: usize, const COLS: usize> $matrix<$t, ROWS, COLS> {
+            /// Returns `true` if the absolute difference of all corresponding
+            /// elements of this matrix and `other` is at most `epsilon`.
+            #[inline]
+            pub fn abs_diff_eq<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+                epsilon: $t,
+            ) -> bool {
+                all_cells(self.array(), other.as_ref(), |x, y| {
+                    x.abs_diff_eq(y, epsilon)
+                })
+            }
+
+            /// Returns `true` if all corresponding elements of this matrix and
+            /// `other` are either within an absolute difference of `epsilon` or
+            /// their difference is at most `max_relative` times the larger of
+            /// their magnitudes.
+            #[inline]
+            pub fn relative_eq<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+                epsilon: $t,
+                max_relative: $t,
+            ) -> bool {
+                all_cells(self.array(), other.as_ref(), |x, y| {
+                    x.relative_eq(y, epsilon, max_relative)
+                })
+            }
+
+            /// Returns `true` if all corresponding elements of this matrix and
+            /// `other` are either within an absolute difference of `epsilon` or
+            /// at most `max_ulps` representable floats apart.
+            #[inline]
+            pub fn ulps_eq<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+                epsilon: $t,
+                max_ulps: u32,
+            ) -> bool {
+                all_cells(self.array(), other.as_ref(), |x, y| {
+                    x.ulps_eq(y, epsilon, max_ulps)
+                })
+            }
+        }
+    };
+}
+
+impl_matrix_approx_eq!(f32, SMatrix);
+impl_matrix_approx_eq!(f32, HMatrix);
+impl_matrix_approx_eq!(f64, SMatrix);
+impl_matrix_approx_eq!(f64, HMatrix);
+
+/// Asserts that two matrices are equal (using `PartialEq`) and prints
+/// both matrices on failure.
+#[macro_export]
+macro_rules! assert_matrix_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !(*left == *right) {
+                    panic!(
+                        "assertion failed: `left == right`\n  left: {:?}\n right: {:?}",
+                        left, right
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that two float matrices are equal up to an absolute
+/// difference of `epsilon` per element and prints both matrices on failure.
+#[macro_export]
+macro_rules! assert_matrix_abs_diff_eq {
+    ($left:expr, $right:expr, $epsilon:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !left.abs_diff_eq(right, $epsilon) {
+                    panic!(
+                        "assertion failed: `left.abs_diff_eq(right, {:?})`\n  left: {:?}\n right: {:?}",
+                        $epsilon, left, right
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that two float matrices are relatively equal (see `relative_eq`)
+/// and prints both matrices on failure.
+#[macro_export]
+macro_rules! assert_matrix_relative_eq {
+    ($left:expr, $right:expr, $epsilon:expr, $max_relative:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !left.relative_eq(right, $epsilon, $max_relative) {
+                    panic!(
+                        "assertion failed: `left.relative_eq(right, {:?}, {:?})`\n  left: {:?}\n right: {:?}",
+                        $epsilon, $max_relative, left, right
+                    );
+                }
+            }
+        }
+    };
+}
+
+/// Asserts that two float matrices are equal up to `max_ulps` units
+/// in the last place (see `ulps_eq`) and prints both matrices on failure.
+#[macro_export]
+macro_rules! assert_matrix_ulps_eq {
+    ($left:expr, $right:expr, $epsilon:expr, $max_ulps:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                if !left.ulps_eq(right, $epsilon, $max_ulps) {
+                    panic!(
+                        "assertion failed: `left.ulps_eq(right, {:?}, {:?})`\n  left: {:?}\n right: {:?}",
+                        $epsilon, $max_ulps, left, right
+                    );
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod matrix_eq_tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_eq() {
+        let a = SMatrix::new([[1, 2], [3, 4]]);
+        let b = HMatrix::new([[1, 2], [3, 4]]);
+        let c = SMatrix::new([[1, 2], [3, 5]]);
+        assert_eq!(a, a);
+        assert_eq!(a, b);
+        assert_eq!(b, a);
+        assert_eq!(b, b.clone());
+        assert_ne!(a, c);
+        assert_ne!(b, c);
+        assert_eq!(MF::<f64, 2, 2>::unit_stack(), MF::<f64, 2, 2>::unit_heap());
+        let mut nan = MF::<f64, 1, 1>::new_stack();
+        nan[0][0] = f64::NAN;
+        assert_ne!(nan, nan);
+    }
+
+    #[test]
+    fn test_hash() {
+        let mut visited = HashMap::new();
+        visited.insert(SMatrix::new([[1, 2], [3, 4]]), 1);
+        visited.insert(SMatrix::new([[4, 3], [2, 1]]), 2);
+        assert_eq!(visited.get(&SMatrix::new([[1, 2], [3, 4]])), Some(&1));
+        assert_eq!(visited.get(&SMatrix::new([[4, 3], [2, 1]])), Some(&2));
+        assert_eq!(visited.get(&SMatrix::new([[0, 0], [0, 0]])), None);
+        let mut heap_keys = HashMap::new();
+        heap_keys.insert(HMatrix::new([[1i8, 2], [3, 4]]), "heap");
+        assert_eq!(
+            heap_keys.get(&HMatrix::new([[1i8, 2], [3, 4]])),
+            Some(&"heap")
+        );
+    }
+
+    #[test]
+    fn test_hash_consistent_with_eq() {
+        use std::collections::hash_map::DefaultHasher;
+        fn hash<H: Hash>(value: &H) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+        let a = SMatrix::new([[7i16, -3], [0, 12]]);
+        assert_eq!(hash(&a), hash(&a.to_heap()));
+    }
+
+    #[test]
+    fn test_abs_diff_eq() {
+        let a = SMatrix::new([[1.0f64, 2.0], [3.0, 4.0]]);
+        let b = HMatrix::new([[1.0f64 + 1e-9, 2.0], [3.0, 4.0 - 1e-9]]);
+        assert!(a.abs_diff_eq(&b, 1e-8));
+        assert!(!a.abs_diff_eq(&b, 1e-10));
+        assert!(b.abs_diff_eq(&a, 1e-8));
+        assert_matrix_abs_diff_eq!(a, b, 1e-8);
+        let c = SMatrix::new([[f32::NAN]]);
+        assert!(!c.abs_diff_eq(&c, 1.0));
+    }
+
+    #[test]
+    fn test_relative_eq() {
+        let a = SMatrix::new([[1.0e10f64, 1.0e-3], [0.0, -5.0]]);
+        let b = SMatrix::new([[1.0e10f64 + 1.0, 1.0e-3], [1e-20, -5.0]]);
+        assert!(a.relative_eq(&b, 1e-15, 1e-9));
+        assert!(!a.relative_eq(&b, 1e-15, 1e-11));
+        assert!(!a.relative_eq(&b, 1e-25, 1e-9));
+        assert_matrix_relative_eq!(a, b.to_heap(), 1e-15, 1e-9);
+        let inf = SMatrix::new([[f64::INFINITY]]);
+        assert!(inf.relative_eq(&inf, 0.0, 0.0));
+        assert!(!inf.relative_eq(&SMatrix::new([[f64::MAX]]), 0.0, 1.0));
+    }
+
+    #[test]
+    fn test_ulps_eq() {
+        let one = 1.0f32;
+        let next = f32::from_bits(one.to_bits() + 2);
+        let a = SMatrix::new([[one, -one]]);
+        let b = SMatrix::new([[next, -next]]);
+        assert!(a.ulps_eq(&b, 0.0, 2));
+        assert!(!a.ulps_eq(&b, 0.0, 1));
+        assert!(a.ulps_eq(&b, 1e-6, 0));
+        assert_matrix_ulps_eq!(a.to_heap(), b, 0.0, 4);
+        let c = SMatrix::new([[0.0f64]]);
+        let d = SMatrix::new([[-0.0f64]]);
+        assert!(c.ulps_eq(&d, 0.0, 0));
+        // NaN is a few million ulps above MAX, on either side
+        let max = SMatrix::new([[f32::MAX]]);
+        let nan = SMatrix::new([[f32::NAN]]);
+        assert!(!max.ulps_eq(&nan, 0.0, u32::MAX));
+        assert!(!nan.ulps_eq(&max, 0.0, u32::MAX));
+        assert!(!nan.ulps_eq(&nan, 0.0, u32::MAX));
+    }
+
+    #[test]
+    fn test_assert_matrix_eq() {
+        assert_matrix_eq!(SMatrix::new([[1, 2]]), HMatrix::new([[1, 2]]));
+    }
+
+    #[test]
+    #[should_panic(expected = "left == right")]
+    fn test_assert_matrix_eq_fails() {
+        assert_matrix_eq!(SMatrix::new([[1, 2]]), SMatrix::new([[1, 3]]));
+    }
+
+    #[test]
+    #[should_panic(expected = "abs_diff_eq")]
+    fn test_assert_matrix_abs_diff_eq_fails() {
+        assert_matrix_abs_diff_eq!(SMatrix::new([[1.0f64]]), SMatrix::new([[1.5]]), 0.1);
+    }
+}
@@ -7,11 +7,14 @@
 
 //! Index and IndexMut implementations
 
+// the reference impls name their lifetime explicitly
+#![allow(clippy::needless_lifetimes)]
+
 use crate::matrix::*;
 use std::ops::{Index, IndexMut};
 
 // A1) Index for SMatrix
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize> for SMatrix<T, ROWS, COLS> {
+impl<T, const ROWS: usize, const COLS: usize> Index<usize> for SMatrix<T, ROWS, COLS> {
     type Output = [T; COLS];
 
     #[inline]
@@ -21,9 +24,7 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize> for SMatr
 }
 
 // A1-Mut) IndexMut for SMatrix
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> IndexMut<usize>
-    for SMatrix<T, ROWS, COLS>
-{
+impl<T, const ROWS: usize, const COLS: usize> IndexMut<usize> for SMatrix<T, ROWS, COLS> {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.array_mut()[index]
@@ -31,9 +32,7 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> IndexMut<usize>
 }
 
 // A2) Index for &SMatrix (shared)
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
-    for &'a SMatrix<T, ROWS, COLS>
-{
+impl<'a, T, const ROWS: usize, const COLS: usize> Index<usize> for &'a SMatrix<T, ROWS, COLS> {
     type Output = [T; COLS];
 
     #[inline]
@@ -43,9 +42,7 @@ impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
 }
 
 // A3) Index for &mut SMatrix (exclusive)
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
-    for &'a mut SMatrix<T, ROWS, COLS>
-{
+impl<'a, T, const ROWS: usize, const COLS: usize> Index<usize> for &'a mut SMatrix<T, ROWS, COLS> {
     type Output = [T; COLS];
 
     #[inline]
@@ -55,7 +52,7 @@ impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
 }
 
 // A3-Mut) IndexMut for &mut SMatrix (exclusive)
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> IndexMut<usize>
+impl<'a, T, const ROWS: usize, const COLS: usize> IndexMut<usize>
     for &'a mut SMatrix<T, ROWS, COLS>
 {
     #[inline]
@@ -65,7 +62,7 @@ impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> IndexMut<usize>
 }
 
 // B1) Index for HMatrix
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize> for HMatrix<T, ROWS, COLS> {
+impl<T, const ROWS: usize, const COLS: usize> Index<usize> for HMatrix<T, ROWS, COLS> {
     type Output = [T; COLS];
 
     #[inline]
@@ -75,9 +72,7 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize> for HMatr
 }
 
 // B1-Mut) IndexMut for HMatrix
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> IndexMut<usize>
-    for HMatrix<T, ROWS, COLS>
-{
+impl<T, const ROWS: usize, const COLS: usize> IndexMut<usize> for HMatrix<T, ROWS, COLS> {
     #[inline]
     fn index_mut(&mut self, index: usize) -> &mut Self::Output {
         &mut self.array_mut()[index]
@@ -85,9 +80,7 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> IndexMut<usize>
 }
 
 // B2) Index for &HMatrix (shared)
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
-    for &'a HMatrix<T, ROWS, COLS>
-{
+impl<'a, T, const ROWS: usize, const COLS: usize> Index<usize> for &'a HMatrix<T, ROWS, COLS> {
     type Output = [T; COLS];
 
     #[inline]
@@ -97,9 +90,7 @@ impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
 }
 
 // B3) Index for &mut HMatrix (exclusive)
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
-    for &'a mut HMatrix<T, ROWS, COLS>
-{
+impl<'a, T, const ROWS: usize, const COLS: usize> Index<usize> for &'a mut HMatrix<T, ROWS, COLS> {
     type Output = [T; COLS];
 
     #[inline]
@@ -109,7 +100,7 @@ impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> Index<usize>
 }
 
 // B3-Mut) IndexMut for &mut HMatrix (exclusive)
-impl<'a, T: Numeric<T>, const ROWS: usize, const COLS: usize> IndexMut<usize>
+impl<'a, T, const ROWS: usize, const COLS: usize> IndexMut<usize>
     for &'a mut HMatrix<T, ROWS, COLS>
 {
     #[inline]
@@ -0,0 +1,153 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Kronecker product
+//!
+//! Stable Rust can't yet express `SMatrix<T, {R1 * R2}, {C1 * C2}>` in a
+//! signature. Instead, the caller names the output type and a const
+//! assertion rejects a wrong shape at compile time.
+
+use crate::matrix::*;
+
+#[inline]
+fn kronecker_into<
+    T: Numeric<T>,
+    const R1: usize,
+    const C1: usize,
+    const R2: usize,
+    const C2: usize,
+    const RO: usize,
+    const CO: usize,
+>(
+    a: &[[T; C1]; R1],
+    b: &[[T; C2]; R2],
+    c: &mut [[T; CO]; RO],
+) {
+    const {
+        assert!(
+            RO == R1 * R2 && CO == C1 * C2,
+            "the shape of a Kronecker product must be (R1 * R2) x (C1 * C2)"
+        )
+    };
+    for (i, row_a) in a.iter().enumerate() {
+        for (j, a_ij) in row_a.iter().enumerate() {
+            for (k, row_b) in b.iter().enumerate() {
+                for (l, b_kl) in row_b.iter().enumerate() {
+                    c[i * R2 + k][j * C2 + l] = *a_ij * *b_kl;
+                }
+            }
+        }
+    }
+}
+
+/// Returns the Kronecker product `a ⊗ b` allocated on the stack. The
+/// output type must be annotated as `SMatrix<T, {R1 * R2}, {C1 * C2}>`;
+/// any other shape doesn't compile.
+///
+/// ```
+/// use const_matrix::matrix::{kronecker, SMatrix};
+/// use const_matrix::smatrix;
+///
+/// let a = smatrix![1, 2; 3, 4];
+/// let b = smatrix![0, 1];
+/// let k: SMatrix<i32, 2, 4> = kronecker(&a, &b);
+/// assert_eq!(k, smatrix![0, 1, 0, 2; 0, 3, 0, 4]);
+/// ```
+///
+/// ```compile_fail
+/// use const_matrix::matrix::{kronecker, SMatrix};
+/// use const_matrix::smatrix;
+///
+/// let k: SMatrix<i32, 2, 2> = kronecker(&smatrix![1, 2; 3, 4], &smatrix![0, 1]);
+/// ```
+#[inline]
+pub fn kronecker<
+    T: Numeric<T>,
+    A: AsRef<[[T; C1]; R1]>,
+    B: AsRef<[[T; C2]; R2]>,
+    const R1: usize,
+    const C1: usize,
+    const R2: usize,
+    const C2: usize,
+    const RO: usize,
+    const CO: usize,
+>(
+    a: &A,
+    b: &B,
+) -> SMatrix<T, RO, CO> {
+    let mut c = MF::<T, RO, CO>::new_stack();
+    kronecker_into(a.as_ref(), b.as_ref(), c.array_mut());
+    c
+}
+
+/// Same as [kronecker](kronecker) but the result is allocated on the heap.
+#[inline]
+pub fn kronecker_heap<
+    T: Numeric<T>,
+    A: AsRef<[[T; C1]; R1]>,
+    B: AsRef<[[T; C2]; R2]>,
+    const R1: usize,
+    const C1: usize,
+    const R2: usize,
+    const C2: usize,
+    const RO: usize,
+    const CO: usize,
+>(
+    a: &A,
+    b: &B,
+) -> HMatrix<T, RO, CO> {
+    let mut c = MF::<T, RO, CO>::new_heap();
+    kronecker_into(a.as_ref(), b.as_ref(), c.array_mut());
+    c
+}
+
+#[cfg(test)]
+mod matrix_kronecker_tests {
+    use super::*;
+
+    #[test]
+    fn test_kronecker() {
+        let a = smatrix![1, 2; 3, 4];
+        let b = smatrix![0, 5; 6, 7];
+        let expected = smatrix![
+             0,  5,  0, 10;
+             6,  7, 12, 14;
+             0, 15,  0, 20;
+            18, 21, 24, 28
+        ];
+        let k: SMatrix<i32, 4, 4> = kronecker(&a, &b);
+        assert_eq!(k, expected);
+        let k: HMatrix<i32, 4, 4> = kronecker_heap(&a.to_heap(), &b);
+        assert_eq!(k, expected);
+        let k: SMatrix<i32, 4, 4> = kronecker(&a, &b.to_heap());
+        assert_eq!(k, expected);
+    }
+
+    #[test]
+    fn test_kronecker_shapes() {
+        let col = svector![1.0, 2.0];
+        let row = rvector![1.0, -1.0, 0.5];
+        let k: SMatrix<f64, 2, 3> = kronecker(&col, &row);
+        assert_eq!(k, smatrix![1.0, -1.0, 0.5; 2.0, -2.0, 1.0]);
+        let unit = MF::<i32, 2, 2>::unit_stack();
+        let k: HMatrix<i32, 6, 6> = kronecker_heap(&MF::<i32, 3, 3>::unit_heap(), &unit);
+        assert_eq!(k, MF::<i32, 6, 6>::unit_stack());
+    }
+
+    #[test]
+    fn test_kronecker_mixed_product() {
+        // (A ⊗ B)(C ⊗ D) = (AC) ⊗ (BD)
+        let a = smatrix![1, 2; 0, 1];
+        let b = smatrix![2, 0; 1, 3];
+        let c = smatrix![1, 0; 4, 1];
+        let d = smatrix![0, 1; 1, 1];
+        let ab: SMatrix<i32, 4, 4> = kronecker(&a, &b);
+        let cd: SMatrix<i32, 4, 4> = kronecker(&c, &d);
+        let acbd: SMatrix<i32, 4, 4> = kronecker(&(a * c), &(b * d));
+        assert_eq!(ab * cd, acbd);
+    }
+}
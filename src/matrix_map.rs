@@ -0,0 +1,205 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Element-wise transformations: map, zip_map, Hadamard product and
+//! element-wise division
+//!
+//! As for `Add`, the result is allocated on the stack if the lhs is an
+//! [SMatrix](SMatrix) and on the heap if the lhs is an [HMatrix](HMatrix).
+//! The rhs can be either of them.
+
+use crate::matrix::*;
+use std::array::from_fn;
+use std::ops::Div;
+
+#[inline]
+fn map_inplace<T: Copy, F: FnMut(T) -> T, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    mut f: F,
+) {
+    for row in a.iter_mut() {
+        for cell in row.iter_mut() {
+            *cell = f(*cell);
+        }
+    }
+}
+
+#[inline]
+fn zip_row<T: Copy, U: Copy, V, F: FnMut(T, U) -> V, const COLS: usize>(
+    a: &[T; COLS],
+    b: &[U; COLS],
+    f: &mut F,
+) -> [V; COLS] {
+    from_fn(|col| f(a[col], b[col]))
+}
+
+impl<T: Copy, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Creates a new stack-allocated matrix by applying `f` to each element
+    /// of this matrix. The element type of the result may differ from `T`.
+    #[inline]
+    pub fn map<U, F: FnMut(T) -> U>(&self, mut f: F) -> SMatrix<U, ROWS, COLS> {
+        SMatrix::new(self.array().map(|row| row.map(&mut f)))
+    }
+
+    /// Replaces each element of this matrix with the result of applying `f` to it.
+    #[inline]
+    pub fn map_inplace<F: FnMut(T) -> T>(&mut self, f: F) {
+        map_inplace(self.array_mut(), f);
+    }
+
+    /// Creates a new stack-allocated matrix by applying `f` to each pair of
+    /// corresponding elements of this matrix and `other`.
+    #[inline]
+    pub fn zip_map<U: Copy, V, M: AsRef<[[U; COLS]; ROWS]>, F: FnMut(T, U) -> V>(
+        &self,
+        other: &M,
+        mut f: F,
+    ) -> SMatrix<V, ROWS, COLS> {
+        let (a, b) = (self.array(), other.as_ref());
+        SMatrix::new(from_fn(|row| zip_row(&a[row], &b[row], &mut f)))
+    }
+}
+
+impl<T: Copy, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Creates a new heap-allocated matrix by applying `f` to each element
+    /// of this matrix. The element type of the result may differ from `T`.
+    #[inline]
+    pub fn map<U, F: FnMut(T) -> U>(&self, mut f: F) -> HMatrix<U, ROWS, COLS> {
+        HMatrix::from_rows(self.array().iter().map(|row| row.map(&mut f)))
+    }
+
+    /// Replaces each element of this matrix with the result of applying `f` to it.
+    #[inline]
+    pub fn map_inplace<F: FnMut(T) -> T>(&mut self, f: F) {
+        map_inplace(self.array_mut(), f);
+    }
+
+    /// Creates a new heap-allocated matrix by applying `f` to each pair of
+    /// corresponding elements of this matrix and `other`.
+    #[inline]
+    pub fn zip_map<U: Copy, V, M: AsRef<[[U; COLS]; ROWS]>, F: FnMut(T, U) -> V>(
+        &self,
+        other: &M,
+        mut f: F,
+    ) -> HMatrix<V, ROWS, COLS> {
+        let b = other.as_ref();
+        HMatrix::from_rows(
+            self.array()
+                .iter()
+                .zip(b.iter())
+                .map(|(row_a, row_b)| zip_row(row_a, row_b, &mut f)),
+        )
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Element-wise (Hadamard) product of this matrix and `other`.
+    #[inline]
+    pub fn hadamard<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> SMatrix<T, ROWS, COLS> {
+        self.zip_map(other, |x, y| x * y)
+    }
+
+    /// Element-wise division of this matrix by `other`.
+    #[inline]
+    pub fn div_elementwise<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> SMatrix<T, ROWS, COLS>
+    where
+        T: Div<Output = T>,
+    {
+        self.zip_map(other, |x, y| x / y)
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Element-wise (Hadamard) product of this matrix and `other`.
+    #[inline]
+    pub fn hadamard<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> HMatrix<T, ROWS, COLS> {
+        self.zip_map(other, |x, y| x * y)
+    }
+
+    /// Element-wise division of this matrix by `other`.
+    #[inline]
+    pub fn div_elementwise<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> HMatrix<T, ROWS, COLS>
+    where
+        T: Div<Output = T>,
+    {
+        self.zip_map(other, |x, y| x / y)
+    }
+}
+
+#[cfg(test)]
+mod matrix_map_tests {
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        let a = SMatrix::new([[-1.0f32, 4.0], [9.0, -16.0]]);
+        let b = a.map(|x| x.abs().sqrt());
+        assert_eq!(b, SMatrix::new([[1.0, 2.0], [3.0, 4.0]]));
+        let relu = a.to_heap().map(|x| x.max(0.0));
+        assert_eq!(relu, SMatrix::new([[0.0, 4.0], [9.0, 0.0]]));
+        // type-changing maps
+        let positive: SMatrix<bool, 2, 2> = a.map(|x| x > 0.0);
+        assert_eq!(positive, SMatrix::new([[false, true], [true, false]]));
+        let wide: HMatrix<f64, 2, 2> = a.to_heap().map(f64::from);
+        assert_eq!(wide[1][1], -16.0f64);
+        let b: HMatrix<bool, 2, 2> = wide.map(|x| x < 0.0);
+        assert!(b[0][0] && !b[0][1]);
+    }
+
+    #[test]
+    fn test_map_inplace() {
+        let mut a = smatrix![1, 2; 3, 4];
+        a.map_inplace(|x| x * x);
+        assert_eq!(a, smatrix![1, 4; 9, 16]);
+        let mut b = hmatrix![1, 2; 3, 4];
+        b.map_inplace(|x| x - 1);
+        assert_eq!(b, smatrix![0, 1; 2, 3]);
+        let mut counter = 0;
+        b.map_inplace(|x| {
+            counter += 1;
+            x + counter
+        });
+        assert_eq!(b, smatrix![1, 3; 5, 7]);
+    }
+
+    #[test]
+    fn test_zip_map() {
+        let a = smatrix![1, 2; 3, 4];
+        let b = hmatrix![4, 3; 2, 1];
+        let max: SMatrix<i32, 2, 2> = a.zip_map(&b, |x, y| x.max(y));
+        assert_eq!(max, smatrix![4, 3; 3, 4]);
+        let max: HMatrix<i32, 2, 2> = b.zip_map(&a, |x, y| x.max(y));
+        assert_eq!(max, smatrix![4, 3; 3, 4]);
+        let eq: SMatrix<bool, 2, 2> = a.zip_map(&a, |x, y| x == y);
+        assert_eq!(eq, smatrix![true, true; true, true]);
+        let mask = smatrix![true, false; false, true];
+        let masked: HMatrix<i32, 2, 2> = b.zip_map(&mask, |x, m| if m { x } else { 0 });
+        assert_eq!(masked, smatrix![4, 0; 0, 1]);
+    }
+
+    #[test]
+    fn test_hadamard() {
+        let a = smatrix![1, 2; 3, 4];
+        let b = hmatrix![5, 6; 7, 8];
+        let expected = smatrix![5, 12; 21, 32];
+        assert_eq!(a.hadamard(&a.clone()), smatrix![1, 4; 9, 16]);
+        assert_eq!(a.hadamard(&b), expected);
+        assert_eq!(b.hadamard(&a), expected);
+        assert_eq!(b.hadamard(&b.clone()), smatrix![25, 36; 49, 64]);
+    }
+
+    #[test]
+    fn test_div_elementwise() {
+        let a = smatrix![1.0, 2.0; 3.0, 4.0];
+        let b = hmatrix![2.0, 4.0; 6.0, 8.0];
+        assert_eq!(a.div_elementwise(&b), smatrix![0.5, 0.5; 0.5, 0.5]);
+        assert_eq!(b.div_elementwise(&a), smatrix![2.0, 2.0; 2.0, 2.0]);
+        assert_eq!(b.div_elementwise(&b.clone()), smatrix![1.0, 1.0; 1.0, 1.0]);
+        let c = smatrix![7, -7];
+        assert_eq!(c.div_elementwise(&smatrix![2, 2]), smatrix![3, -3]);
+    }
+}
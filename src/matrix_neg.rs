@@ -11,7 +11,7 @@ use crate::matrix::*;
 use std::ops::Neg;
 
 #[inline]
-fn neg<T: Numeric<T>, const ROWS: usize, const COLS: usize>(
+fn neg<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize>(
     a: &[[T; COLS]; ROWS],
     b: &mut [[T; COLS]; ROWS],
 ) {
@@ -22,7 +22,9 @@ fn neg<T: Numeric<T>, const ROWS: usize, const COLS: usize>(
     }
 }
 
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for SMatrix<T, ROWS, COLS> {
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize> Neg
+    for SMatrix<T, ROWS, COLS>
+{
     type Output = SMatrix<T, ROWS, COLS>;
 
     //noinspection ALL
@@ -34,7 +36,9 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for SMatrix<T, ROW
     }
 }
 
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for &SMatrix<T, ROWS, COLS> {
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize> Neg
+    for &SMatrix<T, ROWS, COLS>
+{
     type Output = SMatrix<T, ROWS, COLS>;
 
     //noinspection ALL
@@ -46,7 +50,9 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for &SMatrix<T, RO
     }
 }
 
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for &mut SMatrix<T, ROWS, COLS> {
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize> Neg
+    for &mut SMatrix<T, ROWS, COLS>
+{
     type Output = SMatrix<T, ROWS, COLS>;
 
     //noinspection ALL
@@ -58,7 +64,9 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for &mut SMatrix<T
     }
 }
 
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for HMatrix<T, ROWS, COLS> {
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize> Neg
+    for HMatrix<T, ROWS, COLS>
+{
     type Output = HMatrix<T, ROWS, COLS>;
 
     //noinspection ALL
@@ -70,7 +78,9 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for HMatrix<T, ROW
     }
 }
 
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for &HMatrix<T, ROWS, COLS> {
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize> Neg
+    for &HMatrix<T, ROWS, COLS>
+{
     type Output = HMatrix<T, ROWS, COLS>;
 
     //noinspection ALL
@@ -82,7 +92,9 @@ impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for &HMatrix<T, RO
     }
 }
 
-impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Neg for &mut HMatrix<T, ROWS, COLS> {
+impl<T: Numeric<T> + Neg<Output = T>, const ROWS: usize, const COLS: usize> Neg
+    for &mut HMatrix<T, ROWS, COLS>
+{
     type Output = HMatrix<T, ROWS, COLS>;
 
     //noinspection ALL
@@ -0,0 +1,351 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Matrix norms
+//!
+//! The norms of float matrices propagate NaN. The Frobenius norm is
+//! computed with a scaled sum of squares (as in LAPACK's `xLASSQ` and
+//! `xNRM2`) so that it neither overflows nor underflows prematurely.
+
+use crate::matrix::*;
+
+const MAX_POWER_ITERATIONS: usize = 100;
+
+/// Scaled sum of squares: returns `(scale, ssq)` such that
+/// `scale² * ssq` is the sum of the squares of the `values`. A NaN yields
+/// `(NaN, 1)`, otherwise an infinite value yields `(∞, 1)`.
+#[inline]
+pub(crate) fn sum_sq_scaled<'a, T: Real + 'a>(values: impl Iterator<Item = &'a T>) -> (T, T) {
+    let mut scale = T::default();
+    let mut ssq = T::one();
+    let mut infinity = None;
+    for &x in values {
+        if x.is_nan() {
+            return (x, T::one());
+        }
+        if !x.is_finite() {
+            // scaling by ∞ would compute ∞ / ∞, keep looking for a NaN
+            infinity = Some(x.abs());
+        } else if infinity.is_none() && x != T::default() {
+            let abs_x = x.abs();
+            if scale < abs_x {
+                let ratio = scale / abs_x;
+                ssq = T::one() + ssq * ratio * ratio;
+                scale = abs_x;
+            } else {
+                let ratio = abs_x / scale;
+                ssq += ratio * ratio;
+            }
+        }
+    }
+    match infinity {
+        Some(inf) => (inf, T::one()),
+        None => (scale, ssq),
+    }
+}
+
+#[inline]
+pub(crate) fn norm_fro<T: Real, const ROWS: usize, const COLS: usize>(a: &[[T; COLS]; ROWS]) -> T {
+    let (scale, ssq) = sum_sq_scaled(a.iter().flatten());
+    scale * ssq.sqrt()
+}
+
+#[inline]
+fn norm_fro_squared<T: Numeric<T>, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+) -> T {
+    let mut sum = T::default();
+    for row in a.iter() {
+        for cell in row.iter() {
+            sum += *cell * *cell;
+        }
+    }
+    sum
+}
+
+/// Maximum that propagates NaN
+#[inline]
+fn max_nan<T: Real>(values: impl Iterator<Item = T>) -> T {
+    let mut max = T::default();
+    for x in values {
+        if x.is_nan() {
+            return x;
+        }
+        if x > max {
+            max = x;
+        }
+    }
+    max
+}
+
+#[inline]
+fn norm_1<T: Real, const ROWS: usize, const COLS: usize>(a: &[[T; COLS]; ROWS]) -> T {
+    max_nan((0..COLS).map(|col| {
+        let mut sum = T::default();
+        for row in a.iter() {
+            sum += row[col].abs();
+        }
+        sum
+    }))
+}
+
+#[inline]
+fn norm_inf<T: Real, const ROWS: usize, const COLS: usize>(a: &[[T; COLS]; ROWS]) -> T {
+    max_nan(a.iter().map(|row| {
+        let mut sum = T::default();
+        for cell in row.iter() {
+            sum += cell.abs();
+        }
+        sum
+    }))
+}
+
+#[inline]
+fn norm_max<T: Real, const ROWS: usize, const COLS: usize>(a: &[[T; COLS]; ROWS]) -> T {
+    max_nan(a.iter().flatten().map(|cell| cell.abs()))
+}
+
+#[inline]
+pub(crate) fn norm_2<T: Real>(x: &[T]) -> T {
+    let (scale, ssq) = sum_sq_scaled(x.iter());
+    scale * ssq.sqrt()
+}
+
+/// Power iteration on `AᵀA` started with the row of `A` that has the
+/// largest norm. Returns a lower bound for the largest singular value.
+fn norm_2_estimate<T: Real, const ROWS: usize, const COLS: usize>(a: &[[T; COLS]; ROWS]) -> T {
+    let mut start: Option<(T, &[T; COLS])> = None;
+    for row in a.iter() {
+        let norm = norm_2(row);
+        if norm.is_nan() {
+            return norm;
+        }
+        if norm > start.map_or(T::default(), |(max_norm, _)| max_norm) {
+            start = Some((norm, row));
+        }
+    }
+    let (start_norm, start_row) = match start {
+        Some(start) => start,
+        None => return T::default(),
+    };
+    // x / ∞ would turn the start vector into 0 * ∞ = NaN below
+    if !start_norm.is_finite() {
+        return start_norm;
+    }
+    let mut x: Vec<T> = start_row.iter().map(|&v| v / start_norm).collect();
+    let mut y = vec![T::default(); ROWS];
+    let mut sigma = T::default();
+    let tolerance = T::EPSILON * T::from_f64(4.0);
+    for _ in 0..MAX_POWER_ITERATIONS {
+        // y = A x
+        for (y_i, row) in y.iter_mut().zip(a.iter()) {
+            *y_i = T::default();
+            for (x_j, a_ij) in x.iter().zip(row.iter()) {
+                *y_i += *a_ij * *x_j;
+            }
+        }
+        let sigma_prev = sigma;
+        sigma = norm_2(&y);
+        if sigma.is_nan() || sigma == T::default() {
+            return sigma;
+        }
+        if (sigma - sigma_prev).abs() <= tolerance * sigma {
+            break;
+        }
+        // x = Aᵀy / ||Aᵀy||
+        for x_j in x.iter_mut() {
+            *x_j = T::default();
+        }
+        for (y_i, row) in y.iter().zip(a.iter()) {
+            for (x_j, a_ij) in x.iter_mut().zip(row.iter()) {
+                *x_j += *a_ij * *y_i;
+            }
+        }
+        let x_norm = norm_2(&x);
+        if x_norm == T::default() {
+            break;
+        }
+        for x_j in x.iter_mut() {
+            *x_j /= x_norm;
+        }
+    }
+    sigma
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Returns the squared Frobenius norm, i.e., the sum of the squares of
+    /// all elements. Useful for integer matrices where no square root exists.
+    #[inline]
+    pub fn norm_fro_squared(&self) -> T {
+        norm_fro_squared(self.array())
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Returns the squared Frobenius norm, i.e., the sum of the squares of
+    /// all elements. Useful for integer matrices where no square root exists.
+    #[inline]
+    pub fn norm_fro_squared(&self) -> T {
+        norm_fro_squared(self.array())
+    }
+}
+
+impl<T: Real, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Returns the Frobenius norm, i.e., the square root of the sum of
+    /// the squares of all elements.
+    #[inline]
+    pub fn norm_fro(&self) -> T {
+        norm_fro(self.array())
+    }
+
+    /// Returns the 1-norm, i.e., the maximum absolute column sum.
+    #[inline]
+    pub fn norm_1(&self) -> T {
+        norm_1(self.array())
+    }
+
+    /// Returns the ∞-norm, i.e., the maximum absolute row sum.
+    #[inline]
+    pub fn norm_inf(&self) -> T {
+        norm_inf(self.array())
+    }
+
+    /// Returns the max-abs norm, i.e., the largest absolute value of all elements.
+    #[inline]
+    pub fn norm_max(&self) -> T {
+        norm_max(self.array())
+    }
+
+    /// Returns an estimate of the spectral norm (the largest singular value)
+    /// computed by power iteration. The estimate never exceeds the spectral
+    /// norm (up to rounding errors).
+    #[inline]
+    pub fn norm_2_estimate(&self) -> T {
+        norm_2_estimate(self.array())
+    }
+}
+
+impl<T: Real, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Returns the Frobenius norm, i.e., the square root of the sum of
+    /// the squares of all elements.
+    #[inline]
+    pub fn norm_fro(&self) -> T {
+        norm_fro(self.array())
+    }
+
+    /// Returns the 1-norm, i.e., the maximum absolute column sum.
+    #[inline]
+    pub fn norm_1(&self) -> T {
+        norm_1(self.array())
+    }
+
+    /// Returns the ∞-norm, i.e., the maximum absolute row sum.
+    #[inline]
+    pub fn norm_inf(&self) -> T {
+        norm_inf(self.array())
+    }
+
+    /// Returns the max-abs norm, i.e., the largest absolute value of all elements.
+    #[inline]
+    pub fn norm_max(&self) -> T {
+        norm_max(self.array())
+    }
+
+    /// Returns an estimate of the spectral norm (the largest singular value)
+    /// computed by power iteration. The estimate never exceeds the spectral
+    /// norm (up to rounding errors).
+    #[inline]
+    pub fn norm_2_estimate(&self) -> T {
+        norm_2_estimate(self.array())
+    }
+}
+
+#[cfg(test)]
+mod matrix_norm_tests {
+    use super::*;
+
+    #[test]
+    fn test_norm_fro() {
+        let a = smatrix![1.0, -2.0; 2.0, 4.0];
+        assert_eq!(a.norm_fro(), 5.0);
+        assert_eq!(a.to_heap().norm_fro(), 5.0);
+        assert_eq!(smatrix![3.0f32; 4.0].norm_fro(), 5.0);
+        assert_eq!(MF::<f64, 3, 3>::new_stack().norm_fro(), 0.0);
+        assert!(smatrix![1.0, f64::NAN].norm_fro().is_nan());
+    }
+
+    #[test]
+    fn test_norm_fro_infinite() {
+        let inf = f64::INFINITY;
+        assert_eq!(smatrix![inf, inf].norm_fro(), inf);
+        assert_eq!(smatrix![-inf; 1.0; inf].norm_fro(), inf);
+        assert_eq!(
+            hmatrix![3.0f32, f32::NEG_INFINITY].norm_fro(),
+            f32::INFINITY
+        );
+        assert!(smatrix![inf, f64::NAN].norm_fro().is_nan());
+        assert!(smatrix![f64::NAN, -inf].norm_fro().is_nan());
+    }
+
+    #[test]
+    fn test_norm_fro_scaled() {
+        // the naive sum of squares would overflow / underflow
+        let big = smatrix![3.0e200, 4.0e200];
+        assert!((big.norm_fro() - 5.0e200).abs() <= 5.0e200 * 1e-15);
+        let tiny = smatrix![3.0e-200; 4.0e-200];
+        assert!((tiny.norm_fro() - 5.0e-200).abs() <= 5.0e-200 * 1e-15);
+        let big32 = smatrix![3.0e30f32, 4.0e30];
+        assert!((big32.norm_fro() - 5.0e30).abs() <= 5.0e30 * 1e-6);
+    }
+
+    #[test]
+    fn test_norm_fro_squared() {
+        assert_eq!(smatrix![1, -2; 3, 4].norm_fro_squared(), 30);
+        assert_eq!(hmatrix![1i64, -2; 3, 4].norm_fro_squared(), 30);
+        assert_eq!(smatrix![1.5, 2.0].norm_fro_squared(), 6.25);
+    }
+
+    #[test]
+    fn test_norm_1_inf_max() {
+        let a = smatrix![1.0, -7.0; -2.0, -3.0];
+        assert_eq!(a.norm_1(), 10.0);
+        assert_eq!(a.norm_inf(), 8.0);
+        assert_eq!(a.norm_max(), 7.0);
+        let h = a.to_heap();
+        assert_eq!(h.norm_1(), 10.0);
+        assert_eq!(h.norm_inf(), 8.0);
+        assert_eq!(h.norm_max(), 7.0);
+        let nan = smatrix![1.0f32, f32::NAN; 5.0, 1.0];
+        assert!(nan.norm_1().is_nan());
+        assert!(nan.norm_inf().is_nan());
+        assert!(nan.norm_max().is_nan());
+    }
+
+    #[test]
+    fn test_norm_2_estimate() {
+        let diag = smatrix![3.0, 0.0; 0.0, -5.0];
+        assert!((diag.norm_2_estimate() - 5.0).abs() < 1e-12);
+        // singular values are 4 and 1
+        let a = smatrix![2.5, 1.5; 1.5, 2.5];
+        assert!((a.norm_2_estimate() - 4.0).abs() < 1e-12);
+        // a start vector of ones would be orthogonal to the singular vector
+        let b = hmatrix![1.0, -1.0];
+        assert!((b.norm_2_estimate() - 2.0f64.sqrt()).abs() < 1e-12);
+        let c = smatrix![1.0f32, 2.0, 3.0; 4.0, 5.0, 6.0];
+        assert!((c.norm_2_estimate() - 9.508_032).abs() < 1e-4);
+        assert!(c.norm_2_estimate() <= c.norm_fro());
+        assert_eq!(MF::<f64, 2, 3>::new_stack().norm_2_estimate(), 0.0);
+        assert!(smatrix![f64::NAN, 1.0].norm_2_estimate().is_nan());
+        let inf = f64::INFINITY;
+        assert_eq!(smatrix![inf, 0.0; 0.0, 1.0].norm_2_estimate(), inf);
+        assert_eq!(
+            hmatrix![1.0f32; f32::NEG_INFINITY].norm_2_estimate(),
+            f32::INFINITY
+        );
+        assert!(smatrix![inf, 0.0; 0.0, f64::NAN].norm_2_estimate().is_nan());
+    }
+}
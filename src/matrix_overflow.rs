@@ -0,0 +1,292 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Checked, wrapping and saturating addition, subtraction and
+//! multiplication for integer matrices
+//!
+//! The `checked_*` methods return `None` as soon as a single element
+//! operation overflows. For products, every partial sum of the dot
+//! products is checked (or wraps, or saturates), so the outcome of the
+//! `checked_mul` and `saturating_mul` can depend on the summation order
+//! which is always by increasing column index.
+//!
+//! Alternatively, the element types `std::num::Wrapping<T>` and
+//! `std::num::Saturating<T>` make all operators wrap or saturate.
+
+use crate::matrix::*;
+
+#[inline]
+fn elementwise<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    b: &[[T; COLS]; ROWS],
+    c: &mut [[T; COLS]; ROWS],
+    op: impl Fn(T, T) -> Option<T>,
+) -> Option<()> {
+    for ((row_a, row_b), row_c) in a.iter().zip(b.iter()).zip(c.iter_mut()) {
+        for ((x, y), z) in row_a.iter().zip(row_b.iter()).zip(row_c.iter_mut()) {
+            *z = op(*x, *y)?;
+        }
+    }
+    Some(())
+}
+
+#[inline]
+fn multiply<T: Copy, const ROWS_LEFT: usize, const COLS_LEFT: usize, const COLS_RIGHT: usize>(
+    a: &[[T; COLS_LEFT]; ROWS_LEFT],
+    b: &[[T; COLS_RIGHT]; COLS_LEFT],
+    c: &mut [[T; COLS_RIGHT]; ROWS_LEFT],
+    mul: impl Fn(T, T) -> Option<T>,
+    add: impl Fn(T, T) -> Option<T>,
+) -> Option<()> {
+    for row_a in 0..ROWS_LEFT {
+        for col_b in 0..COLS_RIGHT {
+            for col_a in 0..COLS_LEFT {
+                c[row_a][col_b] = add(c[row_a][col_b], mul(a[row_a][col_a], b[col_a][col_b])?)?;
+            }
+        }
+    }
+    Some(())
+}
+
+macro_rules! impl_overflow_arith {
+    ($($t:ty),*) => {
+        $(
+            impl_overflow_arith!(@impl $t, SMatrix, new_stack);
+            impl_overflow_arith!(@impl $t, HMatrix, new_heap);
+        )*
+    };
+    (@impl $t:ty, $matrix:ident, $new:ident) => {
+        impl<const ROWS: usize, const COLS: usize> $matrix<$t, ROWS, COLS> {
+            /// Element-wise sum or `None` if any element overflows.
+            #[inline]
+            pub fn checked_add<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+            ) -> Option<$matrix<$t, ROWS, COLS>> {
+                let mut c = MF::<$t, ROWS, COLS>::$new();
+                elementwise(self.array(), other.as_ref(), c.array_mut(), <$t>::checked_add)?;
+                Some(c)
+            }
+
+            /// Element-wise difference or `None` if any element overflows.
+            #[inline]
+            pub fn checked_sub<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+            ) -> Option<$matrix<$t, ROWS, COLS>> {
+                let mut c = MF::<$t, ROWS, COLS>::$new();
+                elementwise(self.array(), other.as_ref(), c.array_mut(), <$t>::checked_sub)?;
+                Some(c)
+            }
+
+            /// Matrix product or `None` if any product or partial sum overflows.
+            #[inline]
+            pub fn checked_mul<M: AsRef<[[$t; COLS_RIGHT]; COLS]>, const COLS_RIGHT: usize>(
+                &self,
+                other: &M,
+            ) -> Option<$matrix<$t, ROWS, COLS_RIGHT>> {
+                let mut c = MF::<$t, ROWS, COLS_RIGHT>::$new();
+                multiply(
+                    self.array(),
+                    other.as_ref(),
+                    c.array_mut(),
+                    <$t>::checked_mul,
+                    <$t>::checked_add,
+                )?;
+                Some(c)
+            }
+
+            /// Element-wise sum wrapping around on overflow.
+            #[inline]
+            pub fn wrapping_add<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+            ) -> $matrix<$t, ROWS, COLS> {
+                let mut c = MF::<$t, ROWS, COLS>::$new();
+                elementwise(self.array(), other.as_ref(), c.array_mut(), |x, y| {
+                    Some(x.wrapping_add(y))
+                });
+                c
+            }
+
+            /// Element-wise difference wrapping around on overflow.
+            #[inline]
+            pub fn wrapping_sub<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+            ) -> $matrix<$t, ROWS, COLS> {
+                let mut c = MF::<$t, ROWS, COLS>::$new();
+                elementwise(self.array(), other.as_ref(), c.array_mut(), |x, y| {
+                    Some(x.wrapping_sub(y))
+                });
+                c
+            }
+
+            /// Matrix product wrapping around on overflow.
+            #[inline]
+            pub fn wrapping_mul<M: AsRef<[[$t; COLS_RIGHT]; COLS]>, const COLS_RIGHT: usize>(
+                &self,
+                other: &M,
+            ) -> $matrix<$t, ROWS, COLS_RIGHT> {
+                let mut c = MF::<$t, ROWS, COLS_RIGHT>::$new();
+                multiply(
+                    self.array(),
+                    other.as_ref(),
+                    c.array_mut(),
+                    |x, y| Some(x.wrapping_mul(y)),
+                    |x, y| Some(x.wrapping_add(y)),
+                );
+                c
+            }
+
+            /// Element-wise sum saturating at the numeric bounds.
+            #[inline]
+            pub fn saturating_add<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+            ) -> $matrix<$t, ROWS, COLS> {
+                let mut c = MF::<$t, ROWS, COLS>::$new();
+                elementwise(self.array(), other.as_ref(), c.array_mut(), |x, y| {
+                    Some(x.saturating_add(y))
+                });
+                c
+            }
+
+            /// Element-wise difference saturating at the numeric bounds.
+            #[inline]
+            pub fn saturating_sub<M: AsRef<[[$t; COLS]; ROWS]>>(
+                &self,
+                other: &M,
+            ) -> $matrix<$t, ROWS, COLS> {
+                let mut c = MF::<$t, ROWS, COLS>::$new();
+                elementwise(self.array(), other.as_ref(), c.array_mut(), |x, y| {
+                    Some(x.saturating_sub(y))
+                });
+                c
+            }
+
+            /// Matrix product where every product and partial sum saturates
+            /// at the numeric bounds.
+            #[inline]
+            pub fn saturating_mul<M: AsRef<[[$t; COLS_RIGHT]; COLS]>, const COLS_RIGHT: usize>(
+                &self,
+                other: &M,
+            ) -> $matrix<$t, ROWS, COLS_RIGHT> {
+                let mut c = MF::<$t, ROWS, COLS_RIGHT>::$new();
+                multiply(
+                    self.array(),
+                    other.as_ref(),
+                    c.array_mut(),
+                    |x, y| Some(x.saturating_mul(y)),
+                    |x, y| Some(x.saturating_add(y)),
+                );
+                c
+            }
+        }
+    };
+}
+
+impl_overflow_arith!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[cfg(test)]
+mod matrix_overflow_tests {
+    use std::num::{Saturating, Wrapping};
+
+    #[test]
+    fn test_checked() {
+        let a = smatrix![100i8, -100; 1, 2];
+        let b = smatrix![27i8, -28; 3, 4];
+        assert_eq!(a.checked_add(&b), Some(smatrix![127, -128; 4, 6]));
+        assert_eq!(a.checked_add(&smatrix![28, 0; 0, 0]), None);
+        assert_eq!(a.to_heap().checked_sub(&b), Some(hmatrix![73, -72; -2, -2]));
+        assert_eq!(b.to_heap().checked_sub(&smatrix![-101, 0; 0, 0]), None);
+        let m = smatrix![10i8, 2; 3, 4];
+        assert_eq!(m.checked_mul(&m), Some(smatrix![106, 28; 42, 22]));
+        assert_eq!(m.checked_mul(&smatrix![13; 0]), None);
+        assert_eq!(
+            m.to_heap().checked_mul(&svector![6, 4]),
+            Some(hmatrix![68; 34])
+        );
+        // the final sum fits, but the partial sum 100 + 100 doesn't
+        assert_eq!(
+            rvector![10i8, 10, -10].checked_mul(&svector![10, 10, 10]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_wrapping() {
+        let a = smatrix![127i8, -128];
+        assert_eq!(a.wrapping_add(&smatrix![1, 0]), smatrix![-128, -128]);
+        assert_eq!(
+            a.to_heap().wrapping_sub(&smatrix![0, 1]),
+            smatrix![127, 127]
+        );
+        let m = smatrix![16i16, 0; 0, 1];
+        assert_eq!(
+            m.wrapping_mul(&smatrix![4096, 1; 0, 1]),
+            smatrix![0, 16; 0, 1]
+        );
+        // wrapping partial sums give the exact result if it fits
+        let r = rvector![10i8, 10, -10].wrapping_mul(&svector![10, 10, 10]);
+        assert_eq!(r, smatrix![100]);
+    }
+
+    #[test]
+    fn test_saturating() {
+        let a = smatrix![120i8, -120];
+        assert_eq!(a.saturating_add(&smatrix![10, -10]), smatrix![127, -128]);
+        assert_eq!(
+            a.to_heap().saturating_sub(&smatrix![-10, 10]),
+            smatrix![127, -128]
+        );
+        let m = hmatrix![100i64, i64::MAX; -1, 1];
+        let p = m.saturating_mul(&smatrix![i64::MAX, 2; 1, 1]);
+        assert_eq!(p, smatrix![i64::MAX, i64::MAX; 1 - i64::MAX, -1]);
+    }
+
+    #[test]
+    fn test_wrapping_elements() {
+        let w = |x: i8| Wrapping(x);
+        let a = smatrix![w(127), w(-128); w(64), w(1)];
+        let b = smatrix![w(1), w(0); w(0), w(1)];
+        assert_eq!(a + b, smatrix![w(-128), w(-128); w(64), w(2)]);
+        assert_eq!(a - b, smatrix![w(126), w(-128); w(64), w(0)]);
+        assert_eq!(-a, smatrix![w(-127), w(-128); w(-64), w(-1)]);
+        assert_eq!(a * b, a);
+        // 127² - 128 * 64 = 7937 = 31 * 256 + 1
+        assert_eq!(a * a, smatrix![w(1), w(0); w(0), w(1)]);
+        let mut c = a.to_heap();
+        c += b;
+        c -= &a;
+        c *= &b;
+        assert_eq!(c, b);
+        c *= w(2);
+        assert_eq!(c, smatrix![w(2), w(0); w(0), w(2)]);
+    }
+
+    #[test]
+    fn test_saturating_elements() {
+        let s = |x: i8| Saturating(x);
+        let a = smatrix![s(127), s(-128); s(64), s(1)];
+        let b = smatrix![s(1), s(0); s(0), s(1)];
+        assert_eq!(a + b, smatrix![s(127), s(-128); s(64), s(2)]);
+        assert_eq!(a - b, smatrix![s(126), s(-128); s(64), s(0)]);
+        assert_eq!(-a, smatrix![s(-127), s(127); s(-64), s(-1)]);
+        assert_eq!(a * b, a);
+        // every product saturates before the sum: 127 + (-128)
+        assert_eq!(a * a, smatrix![s(-1), s(-128); s(127), s(-127)]);
+        // saturated results aren't invertible: (a + b) - b != a
+        let mut c = a.to_heap();
+        c += b;
+        c -= &b;
+        assert_eq!(c, smatrix![s(126), s(-128); s(64), s(1)]);
+        c *= &b;
+        c *= s(2);
+        assert_eq!(c, smatrix![s(127), s(-128); s(127), s(2)]);
+    }
+}
@@ -0,0 +1,488 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! FromStr implementations
+//!
+//! Two syntaxes are accepted:
+//!
+//! - MATLAB-style: `[1 2 3; 4 5 6]` where rows are separated by `;` or
+//!   line breaks and elements by whitespace or `,`. The enclosing brackets
+//!   are optional.
+//! - Nested brackets as in JSON or NumPy: `[[1, 2, 3], [4, 5, 6]]`.
+
+use crate::matrix::*;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+/// The error returned when parsing a matrix from a string fails.
+/// Lines and columns are counted from 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseMatrixError {
+    /// The input is not a well-formed matrix literal.
+    Syntax {
+        /// What was expected at the offending position
+        message: &'static str,
+        /// The line of the offending position
+        line: usize,
+        /// The column of the offending position
+        column: usize,
+    },
+    /// An element could not be parsed into the element type.
+    InvalidElement {
+        /// The text of the element
+        token: String,
+        /// The line where the element starts
+        line: usize,
+        /// The column where the element starts
+        column: usize,
+    },
+    /// The number of rows or columns doesn't match the matrix type.
+    Shape {
+        /// The `(ROWS, COLS)` of the matrix type
+        expected: (usize, usize),
+        /// The number of rows found and the number of columns of the offending row
+        found: (usize, usize),
+        /// The line of the offending row
+        line: usize,
+        /// The column of the offending row
+        column: usize,
+    },
+}
+
+impl Display for ParseMatrixError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseMatrixError::Syntax {
+                message,
+                line,
+                column,
+            } => write!(f, "{} at line {}, column {}", message, line, column),
+            ParseMatrixError::InvalidElement {
+                token,
+                line,
+                column,
+            } => write!(
+                f,
+                "invalid matrix element \"{}\" at line {}, column {}",
+                token, line, column
+            ),
+            ParseMatrixError::Shape {
+                expected,
+                found,
+                line,
+                column,
+            } => write!(
+                f,
+                "expected a {}x{} matrix but found {}x{} at line {}, column {}",
+                expected.0, expected.1, found.0, found.1, line, column
+            ),
+        }
+    }
+}
+
+impl Error for ParseMatrixError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Open,
+    Close,
+    Comma,
+    Semicolon,
+    Newline,
+    Element,
+    End,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'s> {
+    kind: Kind,
+    text: &'s str,
+    line: usize,
+    column: usize,
+}
+
+fn tokenize(s: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let (mut line, mut column) = (1, 1);
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let kind = match c {
+            '[' => Kind::Open,
+            ']' => Kind::Close,
+            ',' => Kind::Comma,
+            ';' => Kind::Semicolon,
+            '\n' => Kind::Newline,
+            c if c.is_whitespace() => {
+                column += 1;
+                continue;
+            }
+            _ => {
+                let mut end = start + c.len_utf8();
+                let mut len = 1;
+                while let Some(&(i, c)) = chars.peek() {
+                    if c.is_whitespace() || "[],;".contains(c) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    len += 1;
+                    chars.next();
+                }
+                tokens.push(Token {
+                    kind: Kind::Element,
+                    text: &s[start..end],
+                    line,
+                    column,
+                });
+                column += len;
+                continue;
+            }
+        };
+        tokens.push(Token {
+            kind,
+            text: &s[start..start + 1],
+            line,
+            column,
+        });
+        if kind == Kind::Newline {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    tokens.push(Token {
+        kind: Kind::End,
+        text: "",
+        line,
+        column,
+    });
+    tokens
+}
+
+/// A parsed row and the position where it starts
+struct Row<'s> {
+    cells: Vec<Token<'s>>,
+    line: usize,
+    column: usize,
+}
+
+struct Parser<'s> {
+    tokens: Vec<Token<'s>>,
+    pos: usize,
+}
+
+impl<'s> Parser<'s> {
+    fn peek(&self) -> Token<'s> {
+        self.tokens[self.pos]
+    }
+
+    fn next(&mut self) -> Token<'s> {
+        let token = self.tokens[self.pos];
+        if token.kind != Kind::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek().kind == Kind::Newline {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, kind: Kind, message: &'static str) -> Result<Token<'s>, ParseMatrixError> {
+        let token = self.next();
+        if token.kind == kind {
+            Ok(token)
+        } else {
+            Err(syntax(message, &token))
+        }
+    }
+
+    fn parse(&mut self) -> Result<Vec<Row<'s>>, ParseMatrixError> {
+        self.skip_newlines();
+        let nested = self.peek().kind == Kind::Open && {
+            let mut i = self.pos + 1;
+            while self.tokens[i].kind == Kind::Newline {
+                i += 1;
+            }
+            self.tokens[i].kind == Kind::Open
+        };
+        let rows = if nested {
+            self.parse_nested()?
+        } else {
+            self.parse_matlab()?
+        };
+        self.skip_newlines();
+        self.expect(Kind::End, "unexpected trailing input")?;
+        Ok(rows)
+    }
+
+    // [[a, b], [c, d]]
+    fn parse_nested(&mut self) -> Result<Vec<Row<'s>>, ParseMatrixError> {
+        let mut rows = Vec::new();
+        self.expect(Kind::Open, "expected '['")?;
+        loop {
+            self.skip_newlines();
+            let open = self.expect(Kind::Open, "expected '[' at the start of a row")?;
+            let mut row = Row {
+                cells: Vec::new(),
+                line: open.line,
+                column: open.column,
+            };
+            self.skip_newlines();
+            if self.peek().kind != Kind::Close {
+                loop {
+                    self.skip_newlines();
+                    row.cells
+                        .push(self.expect(Kind::Element, "expected a matrix element")?);
+                    self.skip_newlines();
+                    match self.next() {
+                        token if token.kind == Kind::Comma => continue,
+                        token if token.kind == Kind::Close => break,
+                        token => return Err(syntax("expected ',' or ']'", &token)),
+                    }
+                }
+            } else {
+                self.next();
+            }
+            rows.push(row);
+            self.skip_newlines();
+            match self.next() {
+                token if token.kind == Kind::Comma => continue,
+                token if token.kind == Kind::Close => break,
+                token => return Err(syntax("expected ',' or ']'", &token)),
+            }
+        }
+        Ok(rows)
+    }
+
+    // [a b; c d] where the brackets are optional
+    fn parse_matlab(&mut self) -> Result<Vec<Row<'s>>, ParseMatrixError> {
+        let bracketed = self.peek().kind == Kind::Open;
+        if bracketed {
+            self.next();
+        }
+        let mut rows = Vec::new();
+        let mut row: Option<Row<'s>> = None;
+        // a comma must be followed by an element, so `1,,2` is rejected
+        let mut after_comma = false;
+        loop {
+            let token = self.next();
+            match token.kind {
+                Kind::Element => {
+                    after_comma = false;
+                    row.get_or_insert(Row {
+                        cells: Vec::new(),
+                        line: token.line,
+                        column: token.column,
+                    })
+                    .cells
+                    .push(token)
+                }
+                _ if after_comma => return Err(syntax("expected a matrix element", &token)),
+                Kind::Comma if row.is_some() => after_comma = true,
+                Kind::Semicolon | Kind::Newline => rows.extend(row.take()),
+                Kind::Close if bracketed => {
+                    rows.extend(row.take());
+                    return Ok(rows);
+                }
+                Kind::End if !bracketed => {
+                    // `next()` doesn't advance past the end
+                    rows.extend(row.take());
+                    return Ok(rows);
+                }
+                Kind::End => return Err(syntax("expected ']'", &token)),
+                _ => return Err(syntax("unexpected character", &token)),
+            }
+        }
+    }
+}
+
+fn syntax(message: &'static str, token: &Token<'_>) -> ParseMatrixError {
+    ParseMatrixError::Syntax {
+        message,
+        line: token.line,
+        column: token.column,
+    }
+}
+
+fn parse_into<T: FromStr, const ROWS: usize, const COLS: usize>(
+    s: &str,
+    target: &mut [[T; COLS]; ROWS],
+) -> Result<(), ParseMatrixError> {
+    let tokens = tokenize(s);
+    let end = tokens[tokens.len() - 1];
+    let rows = Parser { tokens, pos: 0 }.parse()?;
+    let found_cols = rows.first().map_or(COLS, |row| row.cells.len());
+    if rows.len() != ROWS {
+        let (line, column) = rows
+            .get(ROWS)
+            .map_or((end.line, end.column), |row| (row.line, row.column));
+        return Err(ParseMatrixError::Shape {
+            expected: (ROWS, COLS),
+            found: (rows.len(), found_cols),
+            line,
+            column,
+        });
+    }
+    for (row, target_row) in rows.iter().zip(target.iter_mut()) {
+        if row.cells.len() != COLS {
+            return Err(ParseMatrixError::Shape {
+                expected: (ROWS, COLS),
+                found: (rows.len(), row.cells.len()),
+                line: row.line,
+                column: row.column,
+            });
+        }
+        for (cell, target_cell) in row.cells.iter().zip(target_row.iter_mut()) {
+            *target_cell = cell
+                .text
+                .parse()
+                .map_err(|_| ParseMatrixError::InvalidElement {
+                    token: cell.text.to_string(),
+                    line: cell.line,
+                    column: cell.column,
+                })?;
+        }
+    }
+    Ok(())
+}
+
+impl<T: Numeric<T> + FromStr, const ROWS: usize, const COLS: usize> FromStr
+    for SMatrix<T, ROWS, COLS>
+{
+    type Err = ParseMatrixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        parse_into(s, m.array_mut())?;
+        Ok(m)
+    }
+}
+
+impl<T: Numeric<T> + FromStr, const ROWS: usize, const COLS: usize> FromStr
+    for HMatrix<T, ROWS, COLS>
+{
+    type Err = ParseMatrixError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        parse_into(s, m.array_mut())?;
+        Ok(m)
+    }
+}
+
+#[cfg(test)]
+mod matrix_parse_tests {
+    use super::*;
+
+    #[test]
+    fn test_matlab_style() {
+        let expected = SMatrix::new([[1, 2, 3], [4, 5, 6]]);
+        let a: SMatrix<i32, 2, 3> = "[1 2 3; 4 5 6]".parse().unwrap();
+        assert_eq!(a, expected);
+        let b: HMatrix<i32, 2, 3> = "[1, 2, 3; 4, 5, 6;]".parse().unwrap();
+        assert_eq!(b, expected);
+        let c: SMatrix<i32, 2, 3> = "1 2 3\n4 5 6\n".parse().unwrap();
+        assert_eq!(c, expected);
+        let d: SMatrix<f64, 2, 2> = "[ -1.5e3  2\n  0.25 -0 ]".parse().unwrap();
+        assert_eq!(d, SMatrix::new([[-1500.0, 2.0], [0.25, 0.0]]));
+        let e: SMatrix<i8, 1, 1> = "7".parse().unwrap();
+        assert_eq!(e[0][0], 7);
+    }
+
+    #[test]
+    fn test_nested_style() {
+        let expected = SMatrix::new([[1.0, 2.0], [3.0, 4.0]]);
+        let a: SMatrix<f64, 2, 2> = "[[1,2],[3,4]]".parse().unwrap();
+        assert_eq!(a, expected);
+        let b: HMatrix<f64, 2, 2> = "[\n  [1.0, 2.0],\n  [3.0, 4.0]\n]\n".parse().unwrap();
+        assert_eq!(b, expected);
+        let c: SMatrix<f32, 0, 0> = "[]".parse().unwrap();
+        assert_eq!(c, MF::<f32, 0, 0>::new_stack());
+    }
+
+    #[test]
+    fn test_shape_errors() {
+        let err = "[1 2 3; 4 5]".parse::<SMatrix<i32, 2, 3>>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseMatrixError::Shape {
+                expected: (2, 3),
+                found: (2, 2),
+                line: 1,
+                column: 9
+            }
+        );
+        let err = "[[1, 2],\n [3, 4],\n [5, 6]]"
+            .parse::<HMatrix<i32, 2, 2>>()
+            .unwrap_err();
+        assert_eq!(
+            err,
+            ParseMatrixError::Shape {
+                expected: (2, 2),
+                found: (3, 2),
+                line: 3,
+                column: 2
+            }
+        );
+        let err = "[1 2]".parse::<SMatrix<i32, 2, 2>>().unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "expected a 2x2 matrix but found 1x2 at line 1, column 6"
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let err = "[1 2; 3 4".parse::<SMatrix<i32, 2, 2>>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseMatrixError::Syntax {
+                message: "expected ']'",
+                line: 1,
+                column: 10
+            }
+        );
+        let err = "[[1, 2], [3, 4]".parse::<SMatrix<i32, 2, 2>>().unwrap_err();
+        assert!(matches!(err, ParseMatrixError::Syntax { column: 16, .. }));
+        let err = "[[1, 2] [3, 4]]".parse::<SMatrix<i32, 2, 2>>().unwrap_err();
+        assert!(matches!(err, ParseMatrixError::Syntax { column: 9, .. }));
+        let err = "[1 2] 3".parse::<SMatrix<i32, 1, 2>>().unwrap_err();
+        assert!(matches!(err, ParseMatrixError::Syntax { column: 7, .. }));
+        let err = "1,,2".parse::<SMatrix<i32, 1, 2>>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseMatrixError::Syntax {
+                message: "expected a matrix element",
+                line: 1,
+                column: 3
+            }
+        );
+        let err = "[1, 2,; 3, 4]".parse::<SMatrix<i32, 2, 2>>().unwrap_err();
+        assert!(matches!(err, ParseMatrixError::Syntax { column: 7, .. }));
+        let err = "[[1,,2]]".parse::<SMatrix<i32, 1, 2>>().unwrap_err();
+        assert!(matches!(err, ParseMatrixError::Syntax { column: 5, .. }));
+    }
+
+    #[test]
+    fn test_invalid_element() {
+        let err = "[1 2;\n 3 x4]".parse::<SMatrix<i32, 2, 2>>().unwrap_err();
+        assert_eq!(
+            err,
+            ParseMatrixError::InvalidElement {
+                token: "x4".to_string(),
+                line: 2,
+                column: 4
+            }
+        );
+        let err = "[[128]]".parse::<SMatrix<i8, 1, 1>>().unwrap_err();
+        assert!(matches!(err, ParseMatrixError::InvalidElement { .. }));
+    }
+}
@@ -0,0 +1,145 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Row and column swaps and permutations
+//!
+//! Swaps work in place. Permutations by a [Permutation](Permutation) return
+//! a new matrix of the same storage kind. Neither of them multiplies.
+
+use crate::matrix::*;
+use crate::permutation::Permutation;
+use std::array::from_fn;
+
+#[inline]
+fn swap_cols<T, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    i: usize,
+    j: usize,
+) {
+    for row in a.iter_mut() {
+        row.swap(i, j);
+    }
+}
+
+#[inline]
+fn permute_row<T: Copy, const COLS: usize>(row: &[T; COLS], p: &Permutation<COLS>) -> [T; COLS] {
+    from_fn(|j| row[p[j]])
+}
+
+impl<T, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Swaps the rows `i` and `j`.
+    #[inline]
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        self.array_mut().swap(i, j);
+    }
+
+    /// Swaps the columns `i` and `j`.
+    #[inline]
+    pub fn swap_cols(&mut self, i: usize, j: usize) {
+        swap_cols(self.array_mut(), i, j);
+    }
+}
+
+impl<T, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Swaps the rows `i` and `j`.
+    #[inline]
+    pub fn swap_rows(&mut self, i: usize, j: usize) {
+        self.array_mut().swap(i, j);
+    }
+
+    /// Swaps the columns `i` and `j`.
+    #[inline]
+    pub fn swap_cols(&mut self, i: usize, j: usize) {
+        swap_cols(self.array_mut(), i, j);
+    }
+}
+
+impl<T: Copy, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Returns the matrix whose row `i` is row `p[i]` of this matrix, i.e.,
+    /// `p.to_matrix() * self`.
+    ///
+    /// ```
+    /// use const_matrix::permutation::Permutation;
+    /// use const_matrix::smatrix;
+    ///
+    /// let p = Permutation::new([2, 0, 1]).unwrap();
+    /// let m = smatrix![1, 1; 2, 2; 3, 3];
+    /// assert_eq!(m.permute_rows(&p), smatrix![3, 3; 1, 1; 2, 2]);
+    /// ```
+    #[inline]
+    pub fn permute_rows(&self, p: &Permutation<ROWS>) -> Self {
+        let a = self.array();
+        SMatrix::new(from_fn(|i| a[p[i]]))
+    }
+
+    /// Returns the matrix whose column `j` is column `p[j]` of this matrix,
+    /// i.e., `self * p.to_matrix().trans()`.
+    #[inline]
+    pub fn permute_cols(&self, p: &Permutation<COLS>) -> Self {
+        SMatrix::new(self.array().map(|row| permute_row(&row, p)))
+    }
+}
+
+impl<T: Copy, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Returns the matrix whose row `i` is row `p[i]` of this matrix, i.e.,
+    /// `p.to_matrix() * self`.
+    #[inline]
+    pub fn permute_rows(&self, p: &Permutation<ROWS>) -> Self {
+        let a = self.array();
+        HMatrix::from_rows(p.as_array().iter().map(|&i| a[i]))
+    }
+
+    /// Returns the matrix whose column `j` is column `p[j]` of this matrix,
+    /// i.e., `self * p.to_matrix().trans()`.
+    #[inline]
+    pub fn permute_cols(&self, p: &Permutation<COLS>) -> Self {
+        HMatrix::from_rows(self.array().iter().map(|row| permute_row(row, p)))
+    }
+}
+
+#[cfg(test)]
+mod matrix_permute_tests {
+    use super::*;
+
+    #[test]
+    fn test_swap() {
+        let mut m = smatrix![1, 2, 3; 4, 5, 6];
+        m.swap_rows(0, 1);
+        assert_eq!(m, smatrix![4, 5, 6; 1, 2, 3]);
+        m.swap_cols(0, 2);
+        assert_eq!(m, smatrix![6, 5, 4; 3, 2, 1]);
+        let mut h = m.to_heap();
+        h.swap_cols(2, 0);
+        h.swap_rows(1, 0);
+        h.swap_rows(1, 1);
+        assert_eq!(h, smatrix![1, 2, 3; 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_permute() {
+        let m = smatrix![1, 2, 3; 4, 5, 6; 7, 8, 9];
+        let p = Permutation::new([1, 2, 0]).unwrap();
+        let pm = p.to_matrix::<i32>();
+        assert_eq!(m.permute_rows(&p), pm * m);
+        assert_eq!(m.permute_cols(&p), m * pm.trans());
+        assert_eq!(m.to_heap().permute_rows(&p), pm * m);
+        assert_eq!(m.to_heap().permute_cols(&p), m * pm.trans());
+        let q = Permutation::new([0, 2, 1]).unwrap();
+        let twice = m.permute_rows(&p).permute_rows(&q);
+        assert_eq!(twice, m.permute_rows(&p.compose(&q)));
+        assert_eq!(m.permute_rows(&p).permute_rows(&p.inverse()), m);
+    }
+
+    #[test]
+    fn test_non_square() {
+        let m = smatrix![1.0, 2.0; 3.0, 4.0; 5.0, 6.0];
+        let rows = Permutation::new([2, 1, 0]).unwrap();
+        let cols = Permutation::new([1, 0]).unwrap();
+        assert_eq!(m.permute_rows(&rows), m.flip_ud());
+        assert_eq!(m.to_heap().permute_cols(&cols), m.flip_lr());
+    }
+}
@@ -0,0 +1,327 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Structural predicates
+//!
+//! `is_square`, `is_diagonal` and the triangular checks are available for
+//! every shape and test for exact zeros. The remaining predicates are only
+//! available for square matrices. For float matrices they compare within an
+//! absolute tolerance `tol`, for integer matrices they are exact and take
+//! no tolerance.
+
+use crate::matrix::*;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+#[inline]
+fn all_cells<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    pred: impl Fn(usize, usize, T) -> bool,
+) -> bool {
+    a.iter()
+        .enumerate()
+        .all(|(i, row)| row.iter().enumerate().all(|(j, &x)| pred(i, j, x)))
+}
+
+#[inline]
+fn is_zero_where<T: Numeric<T> + PartialEq, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    pred: impl Fn(usize, usize) -> bool,
+) -> bool {
+    all_cells(a, |i, j, x| !pred(i, j) || x == T::default())
+}
+
+// Compares every element strictly above the diagonal with its mirror image
+#[inline]
+fn all_pairs<T: Copy, const N: usize>(a: &[[T; N]; N], pred: impl Fn(T, T) -> bool) -> bool {
+    (0..N).all(|i| (i + 1..N).all(|j| pred(a[i][j], a[j][i])))
+}
+
+#[inline]
+fn dot<T: Real, const N: usize>(x: &[T; N], y: &[T; N]) -> T {
+    x.iter()
+        .zip(y.iter())
+        .fold(T::default(), |acc, (&u, &v)| acc + u * v)
+}
+
+#[inline]
+fn is_orthogonal<T: Real, const N: usize>(a: &[[T; N]; N], tol: T) -> bool {
+    (0..N).all(|i| {
+        (i..N).all(|j| {
+            let expected = if i == j { T::one() } else { T::default() };
+            (dot(&a[i], &a[j]) - expected).abs() <= tol
+        })
+    })
+}
+
+/// Trial Cholesky decomposition (row by row) of the lower triangle of `a(i, j)`.
+fn cholesky_succeeds<T: Real, const N: usize>(a: impl Fn(usize, usize) -> T) -> bool {
+    let mut l = vec![[T::default(); N]; N];
+    for j in 0..N {
+        let (done, rest) = l.split_at_mut(j);
+        let row_j = &mut rest[0];
+        for (i, row_i) in done.iter().enumerate() {
+            let s = dot_prefix(row_i, row_j, i);
+            row_j[i] = (a(j, i) - s) / row_i[i];
+        }
+        let d = a(j, j) - dot_prefix(row_j, row_j, j);
+        // also rejects NaN
+        if d.partial_cmp(&T::default()) != Some(Ordering::Greater) {
+            return false;
+        }
+        row_j[j] = d.sqrt();
+    }
+    true
+}
+
+#[inline]
+fn dot_prefix<T: Real, const N: usize>(x: &[T; N], y: &[T; N], len: usize) -> T {
+    x[..len]
+        .iter()
+        .zip(y[..len].iter())
+        .fold(T::default(), |acc, (&u, &v)| acc + u * v)
+}
+
+/// Sylvester's criterion with fraction-free (Bareiss) elimination of the
+/// symmetric matrix given by its lower triangle `a(i, j)`: the pivots are
+/// the leading principal minors, and every division is exact. Returns
+/// `None` if an element or an intermediate value doesn't fit in `i128`.
+fn leading_minors_positive<const N: usize>(
+    a: impl Fn(usize, usize) -> Option<i128>,
+) -> Option<bool> {
+    let mut m = (0..N)
+        .map(|i| (0..N).map(|j| a(i.max(j), i.min(j))).collect())
+        .collect::<Option<Vec<Vec<i128>>>>()?;
+    let mut prev = 1i128;
+    for k in 0..N {
+        let pivot = m[k][k];
+        if pivot <= 0 {
+            return Some(false);
+        }
+        for i in k + 1..N {
+            for j in k + 1..N {
+                let x = pivot
+                    .checked_mul(m[i][j])?
+                    .checked_sub(m[i][k].checked_mul(m[k][j])?)?;
+                m[i][j] = x / prev;
+            }
+        }
+        prev = pivot;
+    }
+    Some(true)
+}
+
+macro_rules! impl_shape_predicates {
+    ($matrix:ident) => {
+        impl<T: Numeric<T> + PartialEq, const ROWS: usize, const COLS: usize>
+            $matrix<T, ROWS, COLS>
+        {
+            /// Returns `true` if the matrix has as many rows as columns.
+            #[inline]
+            pub fn is_square(&self) -> bool {
+                ROWS == COLS
+            }
+
+            /// Returns `true` if all elements off the main diagonal are zero.
+            #[inline]
+            pub fn is_diagonal(&self) -> bool {
+                is_zero_where(self.array(), |i, j| i != j)
+            }
+
+            /// Returns `true` if all elements below the main diagonal are zero.
+            #[inline]
+            pub fn is_upper_triangular(&self) -> bool {
+                is_zero_where(self.array(), |i, j| i > j)
+            }
+
+            /// Returns `true` if all elements above the main diagonal are zero.
+            #[inline]
+            pub fn is_lower_triangular(&self) -> bool {
+                is_zero_where(self.array(), |i, j| i < j)
+            }
+        }
+
+        impl<T: Real, const N: usize> $matrix<T, N, N> {
+            /// Returns `true` if `|self[i][j] - self[j][i]| <= tol` for all
+            /// `i` and `j`.
+            #[inline]
+            pub fn is_symmetric(&self, tol: T) -> bool {
+                all_pairs(self.array(), |x, y| (x - y).abs() <= tol)
+            }
+
+            /// Returns `true` if `|self[i][j] + self[j][i]| <= tol` for all
+            /// `i` and `j` (which includes `|self[i][i]| <= tol / 2`).
+            #[inline]
+            pub fn is_skew_symmetric(&self, tol: T) -> bool {
+                let a = self.array();
+                all_pairs(a, |x, y| (x + y).abs() <= tol)
+                    && (0..N).all(|i| (a[i][i] + a[i][i]).abs() <= tol)
+            }
+
+            /// Returns `true` if every element differs from the corresponding
+            /// element of the identity matrix by at most `tol`.
+            #[inline]
+            pub fn is_identity(&self, tol: T) -> bool {
+                all_cells(self.array(), |i, j, x| {
+                    let expected = if i == j { T::one() } else { T::default() };
+                    (x - expected).abs() <= tol
+                })
+            }
+
+            /// Returns `true` if every element of `self * selfᵀ` differs from
+            /// the corresponding element of the identity matrix by at most
+            /// `tol`.
+            #[inline]
+            pub fn is_orthogonal(&self, tol: T) -> bool {
+                is_orthogonal(self.array(), tol)
+            }
+
+            /// Returns `true` if a Cholesky decomposition of the matrix exists.
+            /// Only the lower triangle is read, so the matrix is assumed to be
+            /// symmetric (see [is_symmetric](Self::is_symmetric)).
+            #[inline]
+            pub fn is_positive_definite(&self) -> bool {
+                let a = self.array();
+                cholesky_succeeds::<T, N>(|i, j| a[i][j])
+            }
+        }
+    };
+}
+
+impl_shape_predicates!(SMatrix);
+impl_shape_predicates!(HMatrix);
+
+macro_rules! impl_int_predicates {
+    ($matrix:ident, $($t:ty),*) => {
+        $(
+        impl<const N: usize> $matrix<$t, N, N> {
+            /// Returns `true` if `self[i][j] == self[j][i]` for all `i` and `j`.
+            #[inline]
+            pub fn is_symmetric(&self) -> bool {
+                all_pairs(self.array(), |x, y| x == y)
+            }
+
+            /// Returns `true` if `self[i][j] == -self[j][i]` for all `i` and
+            /// `j` (which implies a zero diagonal).
+            #[inline]
+            pub fn is_skew_symmetric(&self) -> bool {
+                let a = self.array();
+                all_pairs(a, |x, y| x.checked_neg() == Some(y))
+                    && (0..N).all(|i| a[i][i] == 0)
+            }
+
+            /// Returns `true` if this is the identity matrix.
+            #[inline]
+            pub fn is_identity(&self) -> bool {
+                all_cells(self.array(), |i, j, x| x == (i == j) as $t)
+            }
+
+            /// Returns `true` if `self * selfᵀ` is the identity matrix, i.e.,
+            /// if this is a permutation matrix with some of its ones negated.
+            #[inline]
+            pub fn is_orthogonal(&self) -> bool {
+                let a = self.array();
+                let is_unit = |x: $t| x == 1 || x.checked_neg() == Some(1);
+                a.iter().all(|row| {
+                    row.iter().filter(|&&x| x != 0).count() == 1
+                        && row.iter().all(|&x| x == 0 || is_unit(x))
+                }) && (0..N).all(|j| a.iter().filter(|row| row[j] != 0).count() == 1)
+            }
+
+            /// Returns `true` if all leading principal minors are positive
+            /// (Sylvester's criterion). Only the lower triangle is read, so
+            /// the matrix is assumed to be symmetric. The check is exact
+            /// unless an element or an intermediate minor exceeds `i128`,
+            /// in which case it falls back to a Cholesky decomposition in
+            /// `f64`.
+            #[inline]
+            pub fn is_positive_definite(&self) -> bool {
+                let a = self.array();
+                match leading_minors_positive::<N>(|i, j| i128::try_from(a[i][j]).ok()) {
+                    Some(positive) => positive,
+                    None => cholesky_succeeds::<f64, N>(|i, j| a[i][j] as f64),
+                }
+            }
+        }
+        )*
+    };
+}
+
+impl_int_predicates!(SMatrix, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+impl_int_predicates!(HMatrix, i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[cfg(test)]
+mod matrix_predicates_tests {
+    use super::*;
+
+    #[test]
+    fn test_shape() {
+        let m = smatrix![1, 2, 3; 0, 4, 5];
+        assert!(!m.is_square());
+        assert!(m.is_upper_triangular());
+        assert!(!m.is_lower_triangular());
+        assert!(!m.is_diagonal());
+        let d = hmatrix![1.0, 0.0; 0.0, 2.0];
+        assert!(d.is_square());
+        assert!(d.is_diagonal() && d.is_upper_triangular() && d.is_lower_triangular());
+        assert!(smatrix![1u8, 0; 7, 1].is_lower_triangular());
+    }
+
+    #[test]
+    fn test_symmetric() {
+        let s = smatrix![1.0, 2.0; 2.0 + 1e-12, 3.0];
+        assert!(s.is_symmetric(1e-9));
+        assert!(!s.is_symmetric(0.0));
+        assert!(smatrix![1i32, 2; 2, 3].is_symmetric());
+        assert!(!hmatrix![1i32, 2; 3, 3].is_symmetric());
+        assert!(smatrix![0i32, 2; -2, 0].is_skew_symmetric());
+        assert!(!smatrix![1i32, 2; -2, 0].is_skew_symmetric());
+        assert!(!smatrix![0u8, 200; 56, 0].is_skew_symmetric());
+        assert!(smatrix![0u8, 0; 0, 0].is_skew_symmetric());
+        assert!(hmatrix![0.0, 2.0; -2.0, 1e-10].is_skew_symmetric(1e-9));
+        assert!(!smatrix![0.0, 2.0; -2.0, 0.1].is_skew_symmetric(1e-9));
+    }
+
+    #[test]
+    fn test_identity_orthogonal() {
+        assert!(MF::<f64, 3, 3>::unit_stack().is_identity(0.0));
+        assert!(smatrix![1.0, 1e-10; 0.0, 1.0].is_identity(1e-9));
+        assert!(smatrix![1i32, 0; 0, 1].is_identity());
+        assert!(!smatrix![1i32, 0; 1, 1].is_identity());
+        let (s, c) = (0.3f64.sin(), 0.3f64.cos());
+        let rot = smatrix![c, -s; s, c];
+        assert!(rot.is_orthogonal(1e-12));
+        assert!(!(rot * 2.0).is_orthogonal(1e-12));
+        assert!(smatrix![0i32, -1, 0; 0, 0, 1; 1, 0, 0].is_orthogonal());
+        assert!(!hmatrix![0i32, 1, 0; 0, 1, 0; 1, 0, 0].is_orthogonal());
+        assert!(!smatrix![1i32, 1; 1, -1].is_orthogonal());
+        assert!(hmatrix![0u16, 1; 1, 0].is_orthogonal());
+    }
+
+    #[test]
+    fn test_positive_definite() {
+        assert!(smatrix![4.0, 2.0; 2.0, 3.0].is_positive_definite());
+        assert!(!smatrix![1.0, 2.0; 2.0, 1.0].is_positive_definite());
+        assert!(!hmatrix![1.0, 0.0; 0.0, 0.0].is_positive_definite());
+        assert!(!smatrix![f64::NAN].is_positive_definite());
+        assert!(smatrix![2i32, -1, 0; -1, 2, -1; 0, -1, 2].is_positive_definite());
+        assert!(!smatrix![2i32, -1, 0; -1, 2, -1; 0, -1, -2].is_positive_definite());
+        assert!(MF::<f32, 4, 4>::hilbert_heap().is_positive_definite());
+        // every element rounds to 2^53 in f64, which is singular
+        let p = 1i64 << 53;
+        assert!(smatrix![p + 1, p; p, p + 1].is_positive_definite());
+        assert!(hmatrix![p as u64 + 1, p as u64; p as u64, p as u64 + 1].is_positive_definite());
+        assert!(!smatrix![p, p + 1; p + 1, p].is_positive_definite());
+        assert!(!smatrix![p, p; p, p].is_positive_definite());
+        // the 2x2 minor overflows i128 and falls back to f64
+        assert!(smatrix![i128::MAX, 0; 0, i128::MAX].is_positive_definite());
+        assert!(smatrix![u128::MAX, 0; 0, 1].is_positive_definite());
+        let big = 1i64 << 40;
+        let m = smatrix![big, 1, 0; 1, big, 1; 0, 1, big];
+        assert!(m.is_positive_definite());
+    }
+}
@@ -0,0 +1,392 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reductions: sum, product, min/max, argmin/argmax, mean and their
+//! per-row and per-column variants
+//!
+//! `min()`, `max()`, `argmin()` and `argmax()` propagate NaN: the first
+//! element that is not comparable to itself (i.e., NaN for floats) is
+//! returned. Otherwise the first occurrence of the extremum is returned.
+//! They panic if the matrix has no elements.
+
+use crate::matrix::*;
+use std::ops::Div;
+
+#[inline]
+fn sum<T: Numeric<T>, const ROWS: usize, const COLS: usize>(a: &[[T; COLS]; ROWS]) -> T {
+    let mut sum = T::default();
+    for row in a.iter() {
+        for cell in row.iter() {
+            sum += *cell;
+        }
+    }
+    sum
+}
+
+#[inline]
+fn product<T: Numeric<T> + One, const ROWS: usize, const COLS: usize>(a: &[[T; COLS]; ROWS]) -> T {
+    let mut product = T::one();
+    for row in a.iter() {
+        for cell in row.iter() {
+            product *= *cell;
+        }
+    }
+    product
+}
+
+/// `n` as a `T` for the computation of means
+#[inline]
+fn count<T: FromUsize>(n: usize) -> T {
+    T::from_usize(n).expect("the number of elements doesn't fit in the element type")
+}
+
+#[inline]
+fn arg_extremum<T: PartialOrd, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    replaces: fn(&T, &T) -> bool,
+) -> (usize, usize) {
+    assert!(ROWS * COLS > 0, "extremum of an empty matrix");
+    let mut arg = (0, 0);
+    for (i, row) in a.iter().enumerate() {
+        for (j, cell) in row.iter().enumerate() {
+            if cell.partial_cmp(cell).is_none() {
+                return (i, j);
+            }
+            if replaces(cell, &a[arg.0][arg.1]) {
+                arg = (i, j);
+            }
+        }
+    }
+    arg
+}
+
+#[inline]
+fn argmin<T: PartialOrd, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+) -> (usize, usize) {
+    arg_extremum(a, |cell, min| cell < min)
+}
+
+#[inline]
+fn argmax<T: PartialOrd, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+) -> (usize, usize) {
+    arg_extremum(a, |cell, max| cell > max)
+}
+
+#[inline]
+fn row_sums<T: Numeric<T>, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+) -> SMatrix<T, ROWS, 1> {
+    let mut sums = MF::<T, ROWS, 1>::new_stack();
+    for (row, sum) in a.iter().zip(sums.array_mut().iter_mut()) {
+        for cell in row.iter() {
+            sum[0] += *cell;
+        }
+    }
+    sums
+}
+
+#[inline]
+fn col_sums<T: Numeric<T>, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+) -> SMatrix<T, 1, COLS> {
+    let mut sums = MF::<T, 1, COLS>::new_stack();
+    for row in a.iter() {
+        for (cell, sum) in row.iter().zip(sums[0].iter_mut()) {
+            *sum += *cell;
+        }
+    }
+    sums
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Returns the sum of all elements.
+    #[inline]
+    pub fn sum(&self) -> T {
+        sum(self.array())
+    }
+
+    /// Returns the product of all elements.
+    #[inline]
+    pub fn product(&self) -> T
+    where
+        T: One,
+    {
+        product(self.array())
+    }
+
+    /// Returns the smallest element (or the first NaN).
+    #[inline]
+    pub fn min(&self) -> T
+    where
+        T: PartialOrd,
+    {
+        let (row, col) = argmin(self.array());
+        self[row][col]
+    }
+
+    /// Returns the largest element (or the first NaN).
+    #[inline]
+    pub fn max(&self) -> T
+    where
+        T: PartialOrd,
+    {
+        let (row, col) = argmax(self.array());
+        self[row][col]
+    }
+
+    /// Returns the `(row, col)` index of the smallest element (or of the first NaN).
+    #[inline]
+    pub fn argmin(&self) -> (usize, usize)
+    where
+        T: PartialOrd,
+    {
+        argmin(self.array())
+    }
+
+    /// Returns the `(row, col)` index of the largest element (or of the first NaN).
+    #[inline]
+    pub fn argmax(&self) -> (usize, usize)
+    where
+        T: PartialOrd,
+    {
+        argmax(self.array())
+    }
+
+    /// Returns the arithmetic mean of all elements. Panics if the number
+    /// of elements doesn't fit in `T` (e.g. more than 127 for `i8`).
+    #[inline]
+    pub fn mean(&self) -> T
+    where
+        T: FromUsize + Div<Output = T>,
+    {
+        self.sum() / count(ROWS * COLS)
+    }
+
+    /// Returns the sums of the rows as a column vector.
+    #[inline]
+    pub fn row_sums(&self) -> SMatrix<T, ROWS, 1> {
+        row_sums(self.array())
+    }
+
+    /// Returns the sums of the columns as a row vector.
+    #[inline]
+    pub fn col_sums(&self) -> SMatrix<T, 1, COLS> {
+        col_sums(self.array())
+    }
+
+    /// Returns the means of the rows as a column vector. Panics if `COLS`
+    /// doesn't fit in `T`.
+    #[inline]
+    pub fn row_means(&self) -> SMatrix<T, ROWS, 1>
+    where
+        T: FromUsize + Div<Output = T>,
+    {
+        let n = count(COLS);
+        row_sums(self.array()).map(|sum| sum / n)
+    }
+
+    /// Returns the means of the columns as a row vector. Panics if `ROWS`
+    /// doesn't fit in `T`.
+    #[inline]
+    pub fn col_means(&self) -> SMatrix<T, 1, COLS>
+    where
+        T: FromUsize + Div<Output = T>,
+    {
+        let n = count(ROWS);
+        col_sums(self.array()).map(|sum| sum / n)
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Returns the sum of all elements.
+    #[inline]
+    pub fn sum(&self) -> T {
+        sum(self.array())
+    }
+
+    /// Returns the product of all elements.
+    #[inline]
+    pub fn product(&self) -> T
+    where
+        T: One,
+    {
+        product(self.array())
+    }
+
+    /// Returns the smallest element (or the first NaN).
+    #[inline]
+    pub fn min(&self) -> T
+    where
+        T: PartialOrd,
+    {
+        let (row, col) = argmin(self.array());
+        self[row][col]
+    }
+
+    /// Returns the largest element (or the first NaN).
+    #[inline]
+    pub fn max(&self) -> T
+    where
+        T: PartialOrd,
+    {
+        let (row, col) = argmax(self.array());
+        self[row][col]
+    }
+
+    /// Returns the `(row, col)` index of the smallest element (or of the first NaN).
+    #[inline]
+    pub fn argmin(&self) -> (usize, usize)
+    where
+        T: PartialOrd,
+    {
+        argmin(self.array())
+    }
+
+    /// Returns the `(row, col)` index of the largest element (or of the first NaN).
+    #[inline]
+    pub fn argmax(&self) -> (usize, usize)
+    where
+        T: PartialOrd,
+    {
+        argmax(self.array())
+    }
+
+    /// Returns the arithmetic mean of all elements. Panics if the number
+    /// of elements doesn't fit in `T` (e.g. more than 127 for `i8`).
+    #[inline]
+    pub fn mean(&self) -> T
+    where
+        T: FromUsize + Div<Output = T>,
+    {
+        self.sum() / count(ROWS * COLS)
+    }
+
+    /// Returns the sums of the rows as a column vector.
+    #[inline]
+    pub fn row_sums(&self) -> SMatrix<T, ROWS, 1> {
+        row_sums(self.array())
+    }
+
+    /// Returns the sums of the columns as a row vector.
+    #[inline]
+    pub fn col_sums(&self) -> SMatrix<T, 1, COLS> {
+        col_sums(self.array())
+    }
+
+    /// Returns the means of the rows as a column vector. Panics if `COLS`
+    /// doesn't fit in `T`.
+    #[inline]
+    pub fn row_means(&self) -> SMatrix<T, ROWS, 1>
+    where
+        T: FromUsize + Div<Output = T>,
+    {
+        let n = count(COLS);
+        row_sums(self.array()).map(|sum| sum / n)
+    }
+
+    /// Returns the means of the columns as a row vector. Panics if `ROWS`
+    /// doesn't fit in `T`.
+    #[inline]
+    pub fn col_means(&self) -> SMatrix<T, 1, COLS>
+    where
+        T: FromUsize + Div<Output = T>,
+    {
+        let n = count(ROWS);
+        col_sums(self.array()).map(|sum| sum / n)
+    }
+}
+
+#[cfg(test)]
+mod matrix_reduce_tests {
+    use super::*;
+
+    #[test]
+    fn test_sum_product() {
+        let a = smatrix![1, 2, 3; 4, 5, 6];
+        assert_eq!(a.sum(), 21);
+        assert_eq!(a.product(), 720);
+        assert_eq!(a.to_heap().sum(), 21);
+        assert_eq!(a.to_heap().product(), 720);
+        assert_eq!(MF::<f64, 0, 3>::new_stack().sum(), 0.0);
+        assert_eq!(MF::<f64, 0, 3>::new_heap().product(), 1.0);
+        // block matrices
+        let unit = MF::<i32, 2, 2>::unit_stack();
+        let blocks = smatrix![unit, unit; unit, unit];
+        assert_eq!(blocks.sum(), MF::<i32, 2, 2>::diag_stack(4));
+        assert_eq!(blocks.product(), unit);
+    }
+
+    #[test]
+    fn test_min_max() {
+        let a = smatrix![3, -1, 4; 1, -5, 9];
+        assert_eq!(a.min(), -5);
+        assert_eq!(a.max(), 9);
+        assert_eq!(a.argmin(), (1, 1));
+        assert_eq!(a.argmax(), (1, 2));
+        let b = hmatrix![2.0, 7.0; 7.0, 2.0];
+        assert_eq!(b.min(), 2.0);
+        assert_eq!(b.max(), 7.0);
+        // first occurrence wins
+        assert_eq!(b.argmin(), (0, 0));
+        assert_eq!(b.argmax(), (0, 1));
+    }
+
+    #[test]
+    fn test_min_max_nan() {
+        let a = smatrix![1.0, f64::NAN; -3.0, f64::NAN];
+        assert!(a.min().is_nan());
+        assert!(a.max().is_nan());
+        assert_eq!(a.argmin(), (0, 1));
+        assert_eq!(a.to_heap().argmax(), (0, 1));
+        let b = smatrix![f32::NAN, 1.0];
+        assert_eq!(b.argmax(), (0, 0));
+        let c = smatrix![f32::NEG_INFINITY, f32::INFINITY];
+        assert_eq!(c.min(), f32::NEG_INFINITY);
+        assert_eq!(c.max(), f32::INFINITY);
+    }
+
+    #[test]
+    #[should_panic(expected = "empty matrix")]
+    fn test_min_empty() {
+        MF::<i32, 3, 0>::new_stack().min();
+    }
+
+    #[test]
+    fn test_mean() {
+        let a = smatrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0];
+        assert_eq!(a.mean(), 3.5);
+        assert_eq!(a.to_heap().mean(), 3.5);
+        assert_eq!(smatrix![1, 2; 3, 5].mean(), 2);
+        // 127 elements still fit in an i8
+        assert_eq!(MF::<i8, 127, 1>::new_stack().mean(), 0);
+        assert_eq!(count::<f32>((1 << 24) + 2), 16777218.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit in the element type")]
+    fn test_mean_count_overflow() {
+        MF::<i8, 16, 8>::new_stack().mean();
+    }
+
+    #[test]
+    fn test_row_col_reductions() {
+        let a = smatrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0];
+        assert_eq!(a.row_sums(), svector![6.0, 15.0]);
+        assert_eq!(a.col_sums(), rvector![5.0, 7.0, 9.0]);
+        assert_eq!(a.row_means(), svector![2.0, 5.0]);
+        assert_eq!(a.col_means(), rvector![2.5, 3.5, 4.5]);
+        let h = a.to_heap();
+        let row_sums: SMatrix<f64, 2, 1> = h.row_sums();
+        let col_means: SMatrix<f64, 1, 3> = h.col_means();
+        assert_eq!(row_sums, svector![6.0, 15.0]);
+        assert_eq!(col_means, rvector![2.5, 3.5, 4.5]);
+        assert_eq!(h.col_sums(), rvector![5.0, 7.0, 9.0]);
+        assert_eq!(h.row_means(), svector![2.0, 5.0]);
+    }
+}
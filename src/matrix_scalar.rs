@@ -0,0 +1,131 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Scalar multiplication, division, addition and subtraction where the
+//! scalar is the right-hand side, e.g. `m * 2.0`, `m / 2.0` or `m + 1.0`
+//!
+//! Since the matrix is the (local) `Self` type here, a single generic
+//! implementation covers all element types, unlike `scalar * matrix`.
+//! Addition and subtraction apply the scalar to every element. The result
+//! is allocated on the stack if the lhs is an [SMatrix](SMatrix) and on the
+//! heap if the lhs is an [HMatrix](HMatrix). An owned lhs is reused for the
+//! result.
+
+use crate::matrix::*;
+use std::ops::{Add, Div, Mul, Sub};
+
+#[inline]
+fn scalar_op<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    scalar: T,
+    b: &mut [[T; COLS]; ROWS],
+    op: impl Fn(T, T) -> T,
+) {
+    for (row_a, row_b) in a.iter().zip(b.iter_mut()) {
+        for (x, y) in row_a.iter().zip(row_b.iter_mut()) {
+            *y = op(*x, scalar);
+        }
+    }
+}
+
+#[inline]
+fn scalar_op_inplace<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    scalar: T,
+    op: impl Fn(T, T) -> T,
+) {
+    for row in a.iter_mut() {
+        for cell in row.iter_mut() {
+            *cell = op(*cell, scalar);
+        }
+    }
+}
+
+macro_rules! impl_scalar_op {
+    ($op:ident, $method:ident, $f:expr, [$($bound:tt)*]) => {
+        impl_scalar_op!(@owned $op, $method, $f, [$($bound)*], SMatrix);
+        impl_scalar_op!(@owned $op, $method, $f, [$($bound)*], HMatrix);
+        impl_scalar_op!(@ref $op, $method, $f, [$($bound)*], &SMatrix<T, ROWS, COLS>, SMatrix, new_stack);
+        impl_scalar_op!(@ref $op, $method, $f, [$($bound)*], &HMatrix<T, ROWS, COLS>, HMatrix, new_heap);
+        impl_scalar_op!(@ref $op, $method, $f, [$($bound)*], &mut SMatrix<T, ROWS, COLS>, SMatrix, new_stack);
+        impl_scalar_op!(@ref $op, $method, $f, [$($bound)*], &mut HMatrix<T, ROWS, COLS>, HMatrix, new_heap);
+    };
+    (@owned $op:ident, $method:ident, $f:expr, [$($bound:tt)*], $matrix:ident) => {
+        impl<T: $($bound)*, const ROWS: usize, const COLS: usize> $op<T> for $matrix<T, ROWS, COLS> {
+            type Output = $matrix<T, ROWS, COLS>;
+            #[inline]
+            fn $method(mut self, rhs: T) -> Self::Output {
+                scalar_op_inplace(self.array_mut(), rhs, $f);
+                self
+            }
+        }
+    };
+    (@ref $op:ident, $method:ident, $f:expr, [$($bound:tt)*], $lhs:ty, $matrix:ident, $new:ident) => {
+        impl<T: $($bound)*, const ROWS: usize, const COLS: usize> $op<T> for $lhs {
+            type Output = $matrix<T, ROWS, COLS>;
+            #[inline]
+            fn $method(self, rhs: T) -> Self::Output {
+                let mut b = MF::<T, ROWS, COLS>::$new();
+                scalar_op(self.array(), rhs, b.array_mut(), $f);
+                b
+            }
+        }
+    };
+}
+
+impl_scalar_op!(Mul, mul, |x, y| x * y, [Numeric<T>]);
+impl_scalar_op!(Div, div, |x, y| x / y, [Numeric<T> + Div<Output = T>]);
+impl_scalar_op!(Add, add, |x, y| x + y, [Numeric<T>]);
+impl_scalar_op!(Sub, sub, |x, y| x - y, [Numeric<T>]);
+
+#[cfg(test)]
+mod matrix_scalar_tests {
+    use crate::complex::Complex;
+
+    #[test]
+    fn test_mul() {
+        let a = smatrix![1.0, 2.0; 3.0, 4.0];
+        let expected = smatrix![2.0, 4.0; 6.0, 8.0];
+        assert_eq!(a * 2.0, expected);
+        assert_eq!(&a * 2.0, expected);
+        assert_eq!(&mut a.clone() * 2.0, expected);
+        assert_eq!(a.to_heap() * 2.0, expected);
+        assert_eq!(&a.to_heap() * 2.0, expected);
+        assert_eq!(&mut a.to_heap() * 2.0, expected);
+        assert_eq!(a * 2.0, 2.0 * a);
+        let b = smatrix![Complex::new(1, 1), Complex::new(0, 2)];
+        assert_eq!(
+            b * Complex::i(),
+            smatrix![Complex::new(-1, 1), Complex::new(-2, 0)]
+        );
+    }
+
+    #[test]
+    fn test_div() {
+        let a = hmatrix![3.0f32, -6.0; 1.5, 0.0];
+        let expected = smatrix![1.0f32, -2.0; 0.5, 0.0];
+        assert_eq!(&a / 3.0, expected);
+        assert_eq!(a.to_stack() / 3.0, expected);
+        assert_eq!(&a.to_stack() / 3.0, expected);
+        assert_eq!(a / 3.0, expected);
+        // integer division truncates
+        assert_eq!(smatrix![7i32, -7] / 2, smatrix![3, -3]);
+        assert_eq!(smatrix![7u8, 255] / 2, smatrix![3u8, 127]);
+    }
+
+    #[test]
+    fn test_add_sub() {
+        let a = smatrix![1.0, 2.0; 3.0, 4.0];
+        assert_eq!(a + 1.0, smatrix![2.0, 3.0; 4.0, 5.0]);
+        assert_eq!(&a - 1.0, smatrix![0.0, 1.0; 2.0, 3.0]);
+        let h = a.to_heap();
+        assert_eq!(&h + 0.5, smatrix![1.5, 2.5; 3.5, 4.5]);
+        assert_eq!(h - 4.0, smatrix![-3.0, -2.0; -1.0, 0.0]);
+        assert_eq!(hmatrix![1u16, 2] + 3, smatrix![4u16, 5]);
+        assert_eq!((a * 2.0 - 1.0) / 2.0 + 0.5, a);
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! `Serialize` and `Deserialize` implementations (behind the `serde` feature)
+//!
+//! Both matrix types serialize as a sequence of `ROWS` rows, each of which
+//! is a sequence of `COLS` elements, e.g. `[[1,2,3],[4,5,6]]` in JSON. So a
+//! serialized `SMatrix` deserializes as an `HMatrix` of the same shape and
+//! vice versa. Deserialization fails if the number of rows or the length of
+//! any row doesn't match the shape. See [serde_flat](crate::serde_flat) for
+//! a flat representation with an explicit shape.
+
+use crate::matrix::*;
+use serde::de::{Deserialize, Deserializer, Error, IgnoredAny, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+use std::convert::TryFrom;
+use std::fmt::{self, Formatter};
+use std::marker::PhantomData;
+
+struct Row<'a, T, const COLS: usize>(&'a [T; COLS]);
+
+impl<T: Serialize, const COLS: usize> Serialize for Row<'_, T, COLS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(COLS))?;
+        for x in self.0.iter() {
+            seq.serialize_element(x)?;
+        }
+        seq.end()
+    }
+}
+
+fn serialize_rows<T: Serialize, S: Serializer, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    let mut seq = serializer.serialize_seq(Some(ROWS))?;
+    for row in a.iter() {
+        seq.serialize_element(&Row(row))?;
+    }
+    seq.end()
+}
+
+// Counts the elements that are left in `seq` after `seen` elements
+fn total_len<'de, A: SeqAccess<'de>>(mut seq: A, seen: usize) -> Result<usize, A::Error> {
+    let mut len = seen;
+    while seq.next_element::<IgnoredAny>()?.is_some() {
+        len += 1;
+    }
+    Ok(len)
+}
+
+struct RowVisitor<T, const COLS: usize>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>, const COLS: usize> Visitor<'de> for RowVisitor<T, COLS> {
+    type Value = [T; COLS];
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "a row of {} elements", COLS)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut row = Vec::with_capacity(COLS);
+        while row.len() < COLS {
+            match seq.next_element()? {
+                Some(x) => row.push(x),
+                None => return Err(A::Error::invalid_length(row.len(), &self)),
+            }
+        }
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(total_len(seq, COLS + 1)?, &self));
+        }
+        Ok(<[T; COLS]>::try_from(row).unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+struct RowSeed<T, const COLS: usize>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>, const COLS: usize> serde::de::DeserializeSeed<'de>
+    for RowSeed<T, COLS>
+{
+    type Value = [T; COLS];
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(RowVisitor(PhantomData))
+    }
+}
+
+struct RowsVisitor<T, const ROWS: usize, const COLS: usize>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>, const ROWS: usize, const COLS: usize> Visitor<'de>
+    for RowsVisitor<T, ROWS, COLS>
+{
+    type Value = Vec<[T; COLS]>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "a {}x{} matrix as {} rows", ROWS, COLS, ROWS)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut rows = Vec::with_capacity(ROWS);
+        while rows.len() < ROWS {
+            match seq.next_element_seed(RowSeed(PhantomData))? {
+                Some(row) => rows.push(row),
+                None => return Err(A::Error::invalid_length(rows.len(), &self)),
+            }
+        }
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(total_len(seq, ROWS + 1)?, &self));
+        }
+        Ok(rows)
+    }
+}
+
+/// Deserializes exactly `ROWS` rows of exactly `COLS` elements.
+pub(crate) fn deserialize_rows<'de, T, D, const ROWS: usize, const COLS: usize>(
+    deserializer: D,
+) -> Result<Vec<[T; COLS]>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_seq(RowsVisitor::<T, ROWS, COLS>(PhantomData))
+}
+
+impl<T: Serialize, const ROWS: usize, const COLS: usize> Serialize for SMatrix<T, ROWS, COLS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_rows(self.array(), serializer)
+    }
+}
+
+impl<T: Serialize, const ROWS: usize, const COLS: usize> Serialize for HMatrix<T, ROWS, COLS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_rows(self.array(), serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const ROWS: usize, const COLS: usize> Deserialize<'de>
+    for SMatrix<T, ROWS, COLS>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rows = deserialize_rows::<T, D, ROWS, COLS>(deserializer)?;
+        Ok(SMatrix::new(
+            <[[T; COLS]; ROWS]>::try_from(rows).unwrap_or_else(|_| unreachable!()),
+        ))
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const ROWS: usize, const COLS: usize> Deserialize<'de>
+    for HMatrix<T, ROWS, COLS>
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rows = deserialize_rows::<T, D, ROWS, COLS>(deserializer)?;
+        Ok(HMatrix::from_rows(rows.into_iter()))
+    }
+}
+
+#[cfg(test)]
+mod matrix_serde_tests {
+    use crate::matrix::*;
+
+    #[test]
+    fn test_round_trip() {
+        let s = smatrix![1, 2, 3; 4, 5, 6];
+        let json = serde_json::to_string(&s).unwrap();
+        assert_eq!(json, "[[1,2,3],[4,5,6]]");
+        let back: SMatrix<i32, 2, 3> = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, s);
+        let h: HMatrix<i32, 2, 3> = serde_json::from_str(&json).unwrap();
+        assert_eq!(h, s);
+        assert_eq!(serde_json::to_string(&h).unwrap(), json);
+        let f = hmatrix![0.5f64, -1.25];
+        let back: SMatrix<f64, 1, 2> =
+            serde_json::from_str(&serde_json::to_string(&f).unwrap()).unwrap();
+        assert_eq!(back, f);
+    }
+
+    #[test]
+    fn test_shape_errors() {
+        let err = |r: Result<SMatrix<i32, 2, 2>, serde_json::Error>| r.unwrap_err().to_string();
+        assert!(err(serde_json::from_str("[[1,2]]"))
+            .starts_with("invalid length 1, expected a 2x2 matrix as 2 rows"));
+        assert!(err(serde_json::from_str("[[1,2],[3,4],[5,6],[7,8]]"))
+            .starts_with("invalid length 4, expected a 2x2 matrix as 2 rows"));
+        assert!(err(serde_json::from_str("[[1,2],[3]]"))
+            .starts_with("invalid length 1, expected a row of 2 elements"));
+        assert!(err(serde_json::from_str("[[1,2],[3,4,5]]"))
+            .starts_with("invalid length 3, expected a row of 2 elements"));
+        let h: Result<HMatrix<i32, 2, 2>, _> = serde_json::from_str("[[1,2],[3,\"x\"]]");
+        assert!(h.is_err());
+    }
+}
@@ -0,0 +1,345 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Shape manipulation: hstack, vstack, reshape, flips, rotate90 and
+//! transpose_in_place
+//!
+//! As for [kronecker](crate::matrix::kronecker), the output shape of
+//! `hstack`, `vstack` and `reshape` is named by the caller and checked by
+//! a const assertion at compile time.
+
+use crate::matrix::*;
+
+#[inline]
+fn hstack_into<T: Copy, const ROWS: usize, const C1: usize, const C2: usize, const CO: usize>(
+    a: &[[T; C1]; ROWS],
+    b: &[[T; C2]; ROWS],
+    c: &mut [[T; CO]; ROWS],
+) {
+    const {
+        assert!(
+            CO == C1 + C2,
+            "the output of hstack must have C1 + C2 columns"
+        )
+    };
+    for ((row_a, row_b), row_c) in a.iter().zip(b.iter()).zip(c.iter_mut()) {
+        row_c[..C1].copy_from_slice(row_a);
+        row_c[C1..].copy_from_slice(row_b);
+    }
+}
+
+#[inline]
+fn vstack_into<T: Copy, const R1: usize, const R2: usize, const RO: usize, const COLS: usize>(
+    a: &[[T; COLS]; R1],
+    b: &[[T; COLS]; R2],
+    c: &mut [[T; COLS]; RO],
+) {
+    const { assert!(RO == R1 + R2, "the output of vstack must have R1 + R2 rows") };
+    c[..R1].copy_from_slice(a);
+    c[R1..].copy_from_slice(b);
+}
+
+#[inline]
+fn reshape_into<T: Copy, const ROWS: usize, const COLS: usize, const R2: usize, const C2: usize>(
+    a: &[[T; COLS]; ROWS],
+    c: &mut [[T; C2]; R2],
+) {
+    const {
+        assert!(
+            ROWS * COLS == R2 * C2,
+            "reshape must preserve the number of elements"
+        )
+    };
+    for k in 0..ROWS * COLS {
+        c[k / C2][k % C2] = a[k / COLS][k % COLS];
+    }
+}
+
+#[inline]
+fn rotate90_into<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    c: &mut [[T; ROWS]; COLS],
+) {
+    for (i, row_c) in c.iter_mut().enumerate() {
+        for (j, cell) in row_c.iter_mut().enumerate() {
+            *cell = a[j][COLS - 1 - i];
+        }
+    }
+}
+
+#[inline]
+fn transpose_in_place<T, const N: usize>(a: &mut [[T; N]; N]) {
+    for i in 0..N {
+        for j in i + 1..N {
+            let (upper, lower) = a.split_at_mut(j);
+            std::mem::swap(&mut upper[i][j], &mut lower[0][i]);
+        }
+    }
+}
+
+/// Places `b` to the right of `a` in a stack-allocated matrix. The output
+/// type must be annotated as `SMatrix<T, ROWS, {C1 + C2}>`.
+///
+/// ```
+/// use const_matrix::matrix::{hstack, SMatrix};
+/// use const_matrix::smatrix;
+///
+/// let m: SMatrix<i32, 2, 3> = hstack(&smatrix![1, 2; 4, 5], &smatrix![3; 6]);
+/// assert_eq!(m, smatrix![1, 2, 3; 4, 5, 6]);
+/// ```
+///
+/// ```compile_fail
+/// use const_matrix::matrix::{hstack, SMatrix};
+/// use const_matrix::smatrix;
+///
+/// let m: SMatrix<i32, 2, 4> = hstack(&smatrix![1, 2; 4, 5], &smatrix![3; 6]);
+/// ```
+#[inline]
+pub fn hstack<
+    T: Numeric<T>,
+    A: AsRef<[[T; C1]; ROWS]>,
+    B: AsRef<[[T; C2]; ROWS]>,
+    const ROWS: usize,
+    const C1: usize,
+    const C2: usize,
+    const CO: usize,
+>(
+    a: &A,
+    b: &B,
+) -> SMatrix<T, ROWS, CO> {
+    let mut c = MF::<T, ROWS, CO>::new_stack();
+    hstack_into(a.as_ref(), b.as_ref(), c.array_mut());
+    c
+}
+
+/// Same as [hstack](hstack) but the result is allocated on the heap.
+#[inline]
+pub fn hstack_heap<
+    T: Numeric<T>,
+    A: AsRef<[[T; C1]; ROWS]>,
+    B: AsRef<[[T; C2]; ROWS]>,
+    const ROWS: usize,
+    const C1: usize,
+    const C2: usize,
+    const CO: usize,
+>(
+    a: &A,
+    b: &B,
+) -> HMatrix<T, ROWS, CO> {
+    let mut c = MF::<T, ROWS, CO>::new_heap();
+    hstack_into(a.as_ref(), b.as_ref(), c.array_mut());
+    c
+}
+
+/// Places `b` below `a` in a stack-allocated matrix. The output type must
+/// be annotated as `SMatrix<T, {R1 + R2}, COLS>`.
+///
+/// ```compile_fail
+/// use const_matrix::matrix::{vstack, SMatrix};
+/// use const_matrix::smatrix;
+///
+/// let m: SMatrix<i32, 2, 2> = vstack(&smatrix![1, 2; 4, 5], &smatrix![3, 6]);
+/// ```
+#[inline]
+pub fn vstack<
+    T: Numeric<T>,
+    A: AsRef<[[T; COLS]; R1]>,
+    B: AsRef<[[T; COLS]; R2]>,
+    const R1: usize,
+    const R2: usize,
+    const RO: usize,
+    const COLS: usize,
+>(
+    a: &A,
+    b: &B,
+) -> SMatrix<T, RO, COLS> {
+    let mut c = MF::<T, RO, COLS>::new_stack();
+    vstack_into(a.as_ref(), b.as_ref(), c.array_mut());
+    c
+}
+
+/// Same as [vstack](vstack) but the result is allocated on the heap.
+#[inline]
+pub fn vstack_heap<
+    T: Numeric<T>,
+    A: AsRef<[[T; COLS]; R1]>,
+    B: AsRef<[[T; COLS]; R2]>,
+    const R1: usize,
+    const R2: usize,
+    const RO: usize,
+    const COLS: usize,
+>(
+    a: &A,
+    b: &B,
+) -> HMatrix<T, RO, COLS> {
+    let mut c = MF::<T, RO, COLS>::new_heap();
+    vstack_into(a.as_ref(), b.as_ref(), c.array_mut());
+    c
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Returns the elements in row-major order as an `R2 x C2` matrix.
+    /// `R2 * C2` must equal `ROWS * COLS`, otherwise this doesn't compile.
+    ///
+    /// ```
+    /// use const_matrix::smatrix;
+    ///
+    /// let m = smatrix![1, 2, 3; 4, 5, 6].reshape::<3, 2>();
+    /// assert_eq!(m, smatrix![1, 2; 3, 4; 5, 6]);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use const_matrix::smatrix;
+    ///
+    /// let m = smatrix![1, 2, 3; 4, 5, 6].reshape::<2, 2>();
+    /// ```
+    #[inline]
+    pub fn reshape<const R2: usize, const C2: usize>(&self) -> SMatrix<T, R2, C2> {
+        let mut c = MF::<T, R2, C2>::new_stack();
+        reshape_into(self.array(), c.array_mut());
+        c
+    }
+
+    /// Returns a copy with the order of the rows reversed.
+    #[inline]
+    pub fn flip_ud(&self) -> Self {
+        let mut c = *self;
+        c.array_mut().reverse();
+        c
+    }
+
+    /// Returns a copy with the order of the columns reversed.
+    #[inline]
+    pub fn flip_lr(&self) -> Self {
+        let mut c = *self;
+        c.array_mut().iter_mut().for_each(|row| row.reverse());
+        c
+    }
+
+    /// Returns this matrix rotated by 90 degrees counterclockwise.
+    #[inline]
+    pub fn rotate90(&self) -> SMatrix<T, COLS, ROWS> {
+        let mut c = MF::<T, COLS, ROWS>::new_stack();
+        rotate90_into(self.array(), c.array_mut());
+        c
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Returns the elements in row-major order as an `R2 x C2` matrix.
+    /// `R2 * C2` must equal `ROWS * COLS`, otherwise this doesn't compile.
+    #[inline]
+    pub fn reshape<const R2: usize, const C2: usize>(&self) -> HMatrix<T, R2, C2> {
+        let mut c = MF::<T, R2, C2>::new_heap();
+        reshape_into(self.array(), c.array_mut());
+        c
+    }
+
+    /// Returns a copy with the order of the rows reversed.
+    #[inline]
+    pub fn flip_ud(&self) -> Self {
+        let mut c = self.clone();
+        c.array_mut().reverse();
+        c
+    }
+
+    /// Returns a copy with the order of the columns reversed.
+    #[inline]
+    pub fn flip_lr(&self) -> Self {
+        let mut c = self.clone();
+        c.array_mut().iter_mut().for_each(|row| row.reverse());
+        c
+    }
+
+    /// Returns this matrix rotated by 90 degrees counterclockwise.
+    #[inline]
+    pub fn rotate90(&self) -> HMatrix<T, COLS, ROWS> {
+        let mut c = MF::<T, COLS, ROWS>::new_heap();
+        rotate90_into(self.array(), c.array_mut());
+        c
+    }
+}
+
+impl<T, const N: usize> SMatrix<T, N, N> {
+    /// Transposes this square matrix without allocating.
+    #[inline]
+    pub fn transpose_in_place(&mut self) {
+        transpose_in_place(self.array_mut());
+    }
+}
+
+impl<T, const N: usize> HMatrix<T, N, N> {
+    /// Transposes this square matrix without allocating.
+    #[inline]
+    pub fn transpose_in_place(&mut self) {
+        transpose_in_place(self.array_mut());
+    }
+}
+
+#[cfg(test)]
+mod matrix_shape_tests {
+    use super::*;
+
+    #[test]
+    fn test_stack() {
+        let a = smatrix![1, 2; 3, 4];
+        let b = smatrix![5; 6];
+        let h: SMatrix<i32, 2, 3> = hstack(&a, &b);
+        assert_eq!(h, smatrix![1, 2, 5; 3, 4, 6]);
+        let h: HMatrix<i32, 2, 3> = hstack_heap(&a.to_heap(), &b);
+        assert_eq!(h, smatrix![1, 2, 5; 3, 4, 6]);
+        let v: SMatrix<i32, 3, 2> = vstack(&a, &rvector![7, 8]);
+        assert_eq!(v, smatrix![1, 2; 3, 4; 7, 8]);
+        let v: HMatrix<i32, 4, 2> = vstack_heap(&a, &a.to_heap());
+        assert_eq!(v, smatrix![1, 2; 3, 4; 1, 2; 3, 4]);
+    }
+
+    #[test]
+    fn test_kkt_blocks() {
+        // [H A'; A 0]
+        let h = smatrix![2.0, 0.0; 0.0, 2.0];
+        let a = rvector![1.0, 1.0];
+        let top: SMatrix<f64, 2, 3> = hstack(&h, &a.trans());
+        let bottom: SMatrix<f64, 1, 3> = hstack(&a, &smatrix![0.0]);
+        let kkt: SMatrix<f64, 3, 3> = vstack(&top, &bottom);
+        assert_eq!(kkt, smatrix![2.0, 0.0, 1.0; 0.0, 2.0, 1.0; 1.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn test_reshape() {
+        let m = smatrix![1, 2, 3, 4, 5, 6];
+        assert_eq!(m.reshape::<2, 3>(), smatrix![1, 2, 3; 4, 5, 6]);
+        assert_eq!(m.reshape::<6, 1>(), svector![1, 2, 3, 4, 5, 6]);
+        let h = m.to_heap().reshape::<3, 2>();
+        assert_eq!(h, smatrix![1, 2; 3, 4; 5, 6]);
+        assert_eq!(h.reshape::<1, 6>(), m);
+    }
+
+    #[test]
+    fn test_flip_rotate() {
+        let m = smatrix![1, 2, 3; 4, 5, 6];
+        assert_eq!(m.flip_ud(), smatrix![4, 5, 6; 1, 2, 3]);
+        assert_eq!(m.flip_lr(), smatrix![3, 2, 1; 6, 5, 4]);
+        assert_eq!(m.to_heap().flip_ud(), smatrix![4, 5, 6; 1, 2, 3]);
+        assert_eq!(m.to_heap().flip_lr(), smatrix![3, 2, 1; 6, 5, 4]);
+        assert_eq!(m.rotate90(), smatrix![3, 6; 2, 5; 1, 4]);
+        let h = m.to_heap();
+        assert_eq!(h.rotate90().rotate90(), m.flip_ud().flip_lr());
+        assert_eq!(h.rotate90().rotate90().rotate90().rotate90(), m);
+    }
+
+    #[test]
+    fn test_transpose_in_place() {
+        let mut m = smatrix![1, 2, 3; 4, 5, 6; 7, 8, 9];
+        let t = m.trans();
+        m.transpose_in_place();
+        assert_eq!(m, t);
+        let mut h = m.to_heap();
+        h.transpose_in_place();
+        assert_eq!(h, t.trans());
+    }
+}
@@ -0,0 +1,173 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Operations on square matrices: trace and integer powers
+//!
+//! Both also work for block matrices whose elements are square
+//! `SMatrix`es themselves.
+
+use crate::matrix::*;
+use crate::matrix_mul::multiply;
+
+#[inline]
+fn trace<T: Numeric<T>, const N: usize>(a: &[[T; N]; N]) -> T {
+    let mut trace = T::default();
+    for (i, row) in a.iter().enumerate() {
+        trace += row[i];
+    }
+    trace
+}
+
+#[inline]
+fn set_identity<T: Numeric<T> + One, const N: usize>(a: &mut [[T; N]; N]) {
+    for (i, row) in a.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = if i == j { T::one() } else { T::default() };
+        }
+    }
+}
+
+#[inline]
+fn set_zero<T: Numeric<T>, const N: usize>(a: &mut [[T; N]; N]) {
+    for row in a.iter_mut() {
+        for cell in row.iter_mut() {
+            *cell = T::default();
+        }
+    }
+}
+
+/// Binary exponentiation (square-and-multiply). `result`, `base` and
+/// `tmp` are caller-provided buffers so that the heap variant doesn't
+/// need to put any `N x N` temporaries on the stack.
+fn pow<T: Numeric<T> + One, const N: usize>(
+    a: &[[T; N]; N],
+    mut n: u32,
+    result: &mut [[T; N]; N],
+    base: &mut [[T; N]; N],
+    tmp: &mut [[T; N]; N],
+) {
+    set_identity(result);
+    base.copy_from_slice(a);
+    while n > 0 {
+        if n & 1 == 1 {
+            set_zero(tmp);
+            multiply(result, base, tmp);
+            result.copy_from_slice(tmp);
+        }
+        n >>= 1;
+        if n > 0 {
+            set_zero(tmp);
+            multiply(base, base, tmp);
+            base.copy_from_slice(tmp);
+        }
+    }
+}
+
+impl<T: Numeric<T>, const N: usize> SMatrix<T, N, N> {
+    /// Returns the trace, i.e., the sum of the diagonal elements.
+    #[inline]
+    pub fn trace(&self) -> T {
+        trace(self.array())
+    }
+
+    /// Returns this matrix raised to the power `n` computed by repeated
+    /// squaring. `pow(0)` is the identity matrix.
+    #[inline]
+    pub fn pow(&self, n: u32) -> SMatrix<T, N, N>
+    where
+        T: One,
+    {
+        let mut result = MF::<T, N, N>::new_stack();
+        let mut base = MF::<T, N, N>::new_stack();
+        let mut tmp = MF::<T, N, N>::new_stack();
+        pow(
+            self.array(),
+            n,
+            result.array_mut(),
+            base.array_mut(),
+            tmp.array_mut(),
+        );
+        result
+    }
+}
+
+impl<T: Numeric<T>, const N: usize> HMatrix<T, N, N> {
+    /// Returns the trace, i.e., the sum of the diagonal elements.
+    #[inline]
+    pub fn trace(&self) -> T {
+        trace(self.array())
+    }
+
+    /// Returns this matrix raised to the power `n` computed by repeated
+    /// squaring. `pow(0)` is the identity matrix.
+    #[inline]
+    pub fn pow(&self, n: u32) -> HMatrix<T, N, N>
+    where
+        T: One,
+    {
+        let mut result = MF::<T, N, N>::new_heap();
+        let mut base = MF::<T, N, N>::new_heap();
+        let mut tmp = MF::<T, N, N>::new_heap();
+        pow(
+            self.array(),
+            n,
+            result.array_mut(),
+            base.array_mut(),
+            tmp.array_mut(),
+        );
+        result
+    }
+}
+
+#[cfg(test)]
+mod matrix_square_tests {
+    use super::*;
+
+    #[test]
+    fn test_trace() {
+        let a = smatrix![1, 2, 3; 4, 5, 6; 7, 8, 9];
+        assert_eq!(a.trace(), 15);
+        assert_eq!(a.to_heap().trace(), 15);
+        assert_eq!(MF::<f64, 0, 0>::new_stack().trace(), 0.0);
+        let unit = MF::<i32, 2, 2>::unit_stack();
+        let blocks = smatrix![unit, unit; unit, unit];
+        // the trace of a block matrix is the sum of its diagonal blocks
+        assert_eq!(blocks.trace(), MF::<i32, 2, 2>::diag_stack(2));
+    }
+
+    #[test]
+    fn test_pow_fibonacci() {
+        let fib = smatrix![1i64, 1; 1, 0];
+        assert_eq!(fib.pow(0), MF::<i64, 2, 2>::unit_stack());
+        assert_eq!(fib.pow(1), fib);
+        assert_eq!(fib.pow(10), smatrix![89, 55; 55, 34]);
+        // F(90) is the largest Fibonacci number whose successor fits into an i64
+        assert_eq!(fib.to_heap().pow(90)[0][1], 2_880_067_194_370_816_120);
+    }
+
+    #[test]
+    fn test_pow() {
+        let a = smatrix![2.0, 1.0; 0.0, 3.0];
+        assert_eq!(a.pow(3), a * a * a);
+        assert_eq!(a.to_heap().pow(5), a * a * a * a * a);
+        let h = hmatrix![1, 1, 0; 0, 1, 1; 0, 0, 1];
+        assert_eq!(h.pow(4), smatrix![1, 4, 6; 0, 1, 4; 0, 0, 1]);
+        assert_eq!(MF::<i32, 0, 0>::new_stack().pow(7), SMatrix::new([]));
+    }
+
+    #[test]
+    fn test_pow_blocks() {
+        let unit = MF::<i32, 2, 2>::unit_stack();
+        let zero = MF::<i32, 2, 2>::new_stack();
+        let two = MF::<i32, 2, 2>::diag_stack(2);
+        let blocks = smatrix![unit, two; zero, unit];
+        assert_eq!(blocks.pow(0), smatrix![unit, zero; zero, unit]);
+        assert_eq!(blocks.pow(3), smatrix![unit, 3 * two; zero, unit]);
+        assert_eq!(blocks.pow(3), blocks * blocks * blocks);
+        assert_eq!(blocks.pow(3).trace(), MF::<i32, 2, 2>::diag_stack(2));
+    }
+}
@@ -0,0 +1,356 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Structured matrix constructors of [MF](MF): Toeplitz, circulant, Hankel,
+//! Vandermonde, Hilbert, companion, diagonal and tridiagonal matrices
+//!
+//! As for `unit_stack` and `unit_heap`, every constructor comes in a
+//! stack-allocated and a heap-allocated form and square constructors only
+//! use `ROWS` of the factory.
+
+use crate::matrix::*;
+use std::ops::Neg;
+
+#[inline]
+fn fill<T, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    f: impl Fn(usize, usize) -> T,
+) {
+    for (i, row) in a.iter_mut().enumerate() {
+        for (j, cell) in row.iter_mut().enumerate() {
+            *cell = f(i, j);
+        }
+    }
+}
+
+#[inline]
+fn toeplitz<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    first_col: &[T; ROWS],
+    first_row: &[T; COLS],
+) {
+    fill(a, |i, j| {
+        if i >= j {
+            first_col[i - j]
+        } else {
+            first_row[j - i]
+        }
+    });
+}
+
+#[inline]
+fn hankel<T: Copy, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    first_col: &[T; ROWS],
+    last_row: &[T; COLS],
+) {
+    fill(a, |i, j| {
+        if i + j < ROWS {
+            first_col[i + j]
+        } else {
+            last_row[i + j + 1 - ROWS]
+        }
+    });
+}
+
+#[inline]
+fn vandermonde<T: Numeric<T> + One, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    points: &[T; ROWS],
+) {
+    for (row, &x) in a.iter_mut().zip(points.iter()) {
+        let mut power = T::one();
+        for cell in row.iter_mut() {
+            *cell = power;
+            power *= x;
+        }
+    }
+}
+
+#[inline]
+fn companion<T: Numeric<T> + One + Neg<Output = T>, const N: usize>(
+    a: &mut [[T; N]; N],
+    coeffs: &[T; N],
+) {
+    for (i, row) in a.iter_mut().enumerate() {
+        if i > 0 {
+            row[i - 1] = T::one();
+        }
+        row[N - 1] = -coeffs[i];
+    }
+}
+
+#[inline]
+fn tridiagonal<T: Copy, const N: usize>(a: &mut [[T; N]; N], sub: &[T], main: &[T; N], sup: &[T]) {
+    let off = N.saturating_sub(1);
+    assert!(
+        sub.len() == off && sup.len() == off,
+        "the sub- and superdiagonal must have {} elements",
+        off
+    );
+    for i in 0..N {
+        a[i][i] = main[i];
+        if i > 0 {
+            a[i][i - 1] = sub[i - 1];
+            a[i - 1][i] = sup[i - 1];
+        }
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> MF<T, ROWS, COLS> {
+    /// Create a Toeplitz matrix with the given first column and first row
+    /// on the stack (`first_row[0]` is ignored in favor of `first_col[0]`).
+    ///
+    /// ```
+    /// use const_matrix::matrix::MF;
+    /// use const_matrix::smatrix;
+    ///
+    /// let t = MF::<i32, 3, 3>::toeplitz_stack(&[1, 2, 3], &[1, 4, 5]);
+    /// assert_eq!(t, smatrix![1, 4, 5; 2, 1, 4; 3, 2, 1]);
+    /// ```
+    #[inline]
+    pub fn toeplitz_stack(first_col: &[T; ROWS], first_row: &[T; COLS]) -> SMatrix<T, ROWS, COLS> {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        toeplitz(m.array_mut(), first_col, first_row);
+        m
+    }
+
+    /// Create a Toeplitz matrix with the given first column and first row
+    /// on the heap (`first_row[0]` is ignored in favor of `first_col[0]`).
+    #[inline]
+    pub fn toeplitz_heap(first_col: &[T; ROWS], first_row: &[T; COLS]) -> HMatrix<T, ROWS, COLS> {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        toeplitz(m.array_mut(), first_col, first_row);
+        m
+    }
+
+    /// Create a circulant matrix with the given first column on the stack.
+    /// Each further column is the previous one rotated down by one.
+    #[inline]
+    pub fn circulant_stack(first_col: &[T; ROWS]) -> SMatrix<T, ROWS, ROWS> {
+        let mut m = MF::<T, ROWS, ROWS>::new_stack();
+        fill(m.array_mut(), |i, j| first_col[(ROWS + i - j) % ROWS]);
+        m
+    }
+
+    /// Create a circulant matrix with the given first column on the heap.
+    /// Each further column is the previous one rotated down by one.
+    #[inline]
+    pub fn circulant_heap(first_col: &[T; ROWS]) -> HMatrix<T, ROWS, ROWS> {
+        let mut m = MF::<T, ROWS, ROWS>::new_heap();
+        fill(m.array_mut(), |i, j| first_col[(ROWS + i - j) % ROWS]);
+        m
+    }
+
+    /// Create a Hankel matrix with the given first column and last row on
+    /// the stack (`last_row[0]` is ignored in favor of `first_col[ROWS - 1]`).
+    #[inline]
+    pub fn hankel_stack(first_col: &[T; ROWS], last_row: &[T; COLS]) -> SMatrix<T, ROWS, COLS> {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        hankel(m.array_mut(), first_col, last_row);
+        m
+    }
+
+    /// Create a Hankel matrix with the given first column and last row on
+    /// the heap (`last_row[0]` is ignored in favor of `first_col[ROWS - 1]`).
+    #[inline]
+    pub fn hankel_heap(first_col: &[T; ROWS], last_row: &[T; COLS]) -> HMatrix<T, ROWS, COLS> {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        hankel(m.array_mut(), first_col, last_row);
+        m
+    }
+
+    /// Create the Vandermonde matrix `m[i][j] = points[i]^j` (increasing
+    /// powers) on the stack.
+    #[inline]
+    pub fn vandermonde_stack(points: &[T; ROWS]) -> SMatrix<T, ROWS, COLS>
+    where
+        T: One,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        vandermonde(m.array_mut(), points);
+        m
+    }
+
+    /// Create the Vandermonde matrix `m[i][j] = points[i]^j` (increasing
+    /// powers) on the heap.
+    #[inline]
+    pub fn vandermonde_heap(points: &[T; ROWS]) -> HMatrix<T, ROWS, COLS>
+    where
+        T: One,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        vandermonde(m.array_mut(), points);
+        m
+    }
+
+    /// Create the Hilbert matrix `m[i][j] = 1 / (i + j + 1)` on the stack.
+    #[inline]
+    pub fn hilbert_stack() -> SMatrix<T, ROWS, COLS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        fill(m.array_mut(), |i, j| {
+            T::one() / T::from_f64((i + j + 1) as f64)
+        });
+        m
+    }
+
+    /// Create the Hilbert matrix `m[i][j] = 1 / (i + j + 1)` on the heap.
+    #[inline]
+    pub fn hilbert_heap() -> HMatrix<T, ROWS, COLS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        fill(m.array_mut(), |i, j| {
+            T::one() / T::from_f64((i + j + 1) as f64)
+        });
+        m
+    }
+
+    /// Create the companion matrix of the monic polynomial
+    /// `x^N + coeffs[N - 1] x^(N - 1) + ... + coeffs[1] x + coeffs[0]` on
+    /// the stack. It has ones on the subdiagonal and `-coeffs` in the last
+    /// column, so its eigenvalues are the roots of the polynomial.
+    ///
+    /// ```
+    /// use const_matrix::matrix::MF;
+    /// use const_matrix::smatrix;
+    ///
+    /// // x² - 3x + 2 = (x - 1)(x - 2)
+    /// let c = MF::<i32, 2, 2>::companion_stack(&[2, -3]);
+    /// assert_eq!(c, smatrix![0, -2; 1, 3]);
+    /// ```
+    #[inline]
+    pub fn companion_stack(coeffs: &[T; ROWS]) -> SMatrix<T, ROWS, ROWS>
+    where
+        T: One + Neg<Output = T>,
+    {
+        let mut m = MF::<T, ROWS, ROWS>::new_stack();
+        companion(m.array_mut(), coeffs);
+        m
+    }
+
+    /// Create the companion matrix of the monic polynomial
+    /// `x^N + coeffs[N - 1] x^(N - 1) + ... + coeffs[1] x + coeffs[0]` on
+    /// the heap.
+    #[inline]
+    pub fn companion_heap(coeffs: &[T; ROWS]) -> HMatrix<T, ROWS, ROWS>
+    where
+        T: One + Neg<Output = T>,
+    {
+        let mut m = MF::<T, ROWS, ROWS>::new_heap();
+        companion(m.array_mut(), coeffs);
+        m
+    }
+
+    /// Create a diagonal matrix with the diagonal `diag` on the stack.
+    #[inline]
+    pub fn from_diagonal_stack(diag: &[T; ROWS]) -> SMatrix<T, ROWS, ROWS> {
+        let mut m = MF::<T, ROWS, ROWS>::new_stack();
+        for (i, &d) in diag.iter().enumerate() {
+            m[i][i] = d;
+        }
+        m
+    }
+
+    /// Create a diagonal matrix with the diagonal `diag` on the heap.
+    #[inline]
+    pub fn from_diagonal_heap(diag: &[T; ROWS]) -> HMatrix<T, ROWS, ROWS> {
+        let mut m = MF::<T, ROWS, ROWS>::new_heap();
+        for (i, &d) in diag.iter().enumerate() {
+            m[i][i] = d;
+        }
+        m
+    }
+
+    /// Create a tridiagonal matrix on the stack. Panics unless `sub` and
+    /// `sup` have `ROWS - 1` elements.
+    #[inline]
+    pub fn tridiagonal_stack(sub: &[T], main: &[T; ROWS], sup: &[T]) -> SMatrix<T, ROWS, ROWS> {
+        let mut m = MF::<T, ROWS, ROWS>::new_stack();
+        tridiagonal(m.array_mut(), sub, main, sup);
+        m
+    }
+
+    /// Create a tridiagonal matrix on the heap. Panics unless `sub` and
+    /// `sup` have `ROWS - 1` elements.
+    #[inline]
+    pub fn tridiagonal_heap(sub: &[T], main: &[T; ROWS], sup: &[T]) -> HMatrix<T, ROWS, ROWS> {
+        let mut m = MF::<T, ROWS, ROWS>::new_heap();
+        tridiagonal(m.array_mut(), sub, main, sup);
+        m
+    }
+}
+
+#[cfg(test)]
+mod matrix_structured_tests {
+    use super::*;
+
+    #[test]
+    fn test_toeplitz_circulant_hankel() {
+        let t = MF::<i32, 2, 3>::toeplitz_heap(&[1, 2], &[9, 3, 4]);
+        assert_eq!(t, smatrix![1, 3, 4; 2, 1, 3]);
+        let c = MF::<i32, 3, 3>::circulant_stack(&[1, 2, 3]);
+        assert_eq!(c, smatrix![1, 3, 2; 2, 1, 3; 3, 2, 1]);
+        assert_eq!(MF::<i32, 3, 3>::circulant_heap(&[1, 2, 3]), c);
+        let h = MF::<i32, 3, 2>::hankel_stack(&[1, 2, 3], &[9, 4]);
+        assert_eq!(h, smatrix![1, 2; 2, 3; 3, 4]);
+        assert_eq!(MF::<i32, 3, 2>::hankel_heap(&[1, 2, 3], &[9, 4]), h);
+    }
+
+    #[test]
+    fn test_vandermonde() {
+        let v = MF::<i64, 3, 4>::vandermonde_stack(&[1, 2, 3]);
+        assert_eq!(v, smatrix![1, 1, 1, 1; 1, 2, 4, 8; 1, 3, 9, 27]);
+        let coeffs = svector![1, 0, -1, 2];
+        // evaluates 1 - x² + 2x³ at the points
+        assert_eq!(
+            MF::<i64, 3, 4>::vandermonde_heap(&[1, 2, 3]) * coeffs,
+            svector![2, 13, 46]
+        );
+    }
+
+    #[test]
+    fn test_hilbert() {
+        let h = MF::<f64, 3, 3>::hilbert_stack();
+        assert_eq!(h[0], [1.0, 0.5, 1.0 / 3.0]);
+        assert_eq!(h[2][2], 0.2);
+        assert_eq!(h, h.trans());
+        assert_eq!(MF::<f64, 3, 3>::hilbert_heap(), h);
+    }
+
+    #[test]
+    fn test_companion() {
+        // x³ - 6x² + 11x - 6 = (x - 1)(x - 2)(x - 3)
+        let c = MF::<f64, 3, 3>::companion_heap(&[-6.0, 11.0, -6.0]);
+        assert_eq!(c, smatrix![0.0, 0.0, 6.0; 1.0, 0.0, -11.0; 0.0, 1.0, 6.0]);
+        // (1, r, r²) is a left eigenvector for every root r
+        let v = rvector![1.0, 2.0, 4.0];
+        assert_eq!(v * &c, v * 2.0);
+        assert_eq!(MF::<f64, 3, 3>::companion_stack(&[-6.0, 11.0, -6.0]), c);
+    }
+
+    #[test]
+    fn test_diagonal() {
+        let d = MF::<i32, 3, 3>::from_diagonal_stack(&[1, 2, 3]);
+        assert_eq!(d, smatrix![1, 0, 0; 0, 2, 0; 0, 0, 3]);
+        assert_eq!(MF::<i32, 3, 3>::from_diagonal_heap(&[1, 2, 3]), d);
+        let t = MF::<i32, 3, 3>::tridiagonal_stack(&[-1, -2], &[2, 2, 2], &[3, 4]);
+        assert_eq!(t, smatrix![2, 3, 0; -1, 2, 4; 0, -2, 2]);
+        let t = MF::<i32, 1, 1>::tridiagonal_heap(&[], &[7], &[]);
+        assert_eq!(t, smatrix![7]);
+    }
+
+    #[test]
+    #[should_panic(expected = "must have 2 elements")]
+    fn test_tridiagonal_wrong_length() {
+        MF::<i32, 3, 3>::tridiagonal_stack(&[1, 2], &[1, 2, 3], &[1]);
+    }
+}
@@ -0,0 +1,294 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Vector operations: dot, norm, normalize, angle_to, cross and outer
+//!
+//! Vectors are plain single-column or single-row matrices (see the
+//! [SVector](SVector) and [SRowVector](SRowVector) aliases), so the `Mul`
+//! impls return vectors again. `dot`, `norm`, `normalize` and `angle_to`
+//! are defined for any shape: for matrices, `dot` is the Frobenius inner
+//! product and `norm` the Frobenius norm. `cross` exists for 3-vectors and
+//! `outer` for column vectors (with row vectors the two would overlap for
+//! `1 x 1` matrices).
+
+use crate::matrix::*;
+use crate::matrix_norm::{norm_2, norm_fro};
+use std::array::from_fn;
+
+#[inline]
+fn dot<T: Numeric<T>, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    b: &[[T; COLS]; ROWS],
+) -> T {
+    let mut dot = T::default();
+    for (row_a, row_b) in a.iter().zip(b.iter()) {
+        for (x, y) in row_a.iter().zip(row_b.iter()) {
+            dot += *x * *y;
+        }
+    }
+    dot
+}
+
+/// Kahan's formula `2 * atan2(‖u - v‖, ‖u + v‖)` for the unit vectors
+/// `u = a / ‖a‖` and `v = b / ‖b‖` which, unlike `acos` of the normalized
+/// dot product, is accurate for small and for nearly straight angles.
+/// Normalizing first keeps the squares in range for huge and tiny vectors.
+/// The angle to a zero vector is NaN.
+fn angle<T: Real, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    b: &[[T; COLS]; ROWS],
+) -> T {
+    let norm_a = norm_fro(a);
+    let norm_b = norm_fro(b);
+    if norm_a == T::default() || norm_b == T::default() {
+        return T::default() / T::default();
+    }
+    let pairs = || {
+        a.iter()
+            .flatten()
+            .zip(b.iter().flatten())
+            .map(|(&x, &y)| (x / norm_a, y / norm_b))
+    };
+    let diff: Vec<T> = pairs().map(|(u, v)| u - v).collect();
+    let sum: Vec<T> = pairs().map(|(u, v)| u + v).collect();
+    T::from_f64(2.0) * norm_2(&diff).atan2(norm_2(&sum))
+}
+
+#[inline]
+fn cross<T: Numeric<T>>(a: [T; 3], b: [T; 3]) -> [T; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+#[inline]
+fn outer_row<T: Numeric<T>, const M: usize>(x: T, b: &[[T; 1]; M]) -> [T; M] {
+    from_fn(|col| x * b[col][0])
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Returns the dot product, i.e., the sum of the products of
+    /// corresponding elements of this vector (or matrix) and `other`.
+    #[inline]
+    pub fn dot<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> T {
+        dot(self.array(), other.as_ref())
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Returns the dot product, i.e., the sum of the products of
+    /// corresponding elements of this vector (or matrix) and `other`.
+    #[inline]
+    pub fn dot<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> T {
+        dot(self.array(), other.as_ref())
+    }
+}
+
+impl<T: Real, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Returns the Euclidean norm (length) of this vector. Same as
+    /// [norm_fro](SMatrix::norm_fro).
+    #[inline]
+    pub fn norm(&self) -> T {
+        self.norm_fro()
+    }
+
+    /// Returns this vector scaled to unit length. The zero vector
+    /// yields NaNs.
+    #[inline]
+    pub fn normalize(&self) -> SMatrix<T, ROWS, COLS> {
+        let norm = self.norm();
+        self.map(|x| x / norm)
+    }
+
+    /// Returns the angle in radians (in `[0, π]`) between this vector and `other`,
+    /// or NaN if either of them is the zero vector.
+    #[inline]
+    pub fn angle_to<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> T {
+        angle(self.array(), other.as_ref())
+    }
+}
+
+impl<T: Real, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Returns the Euclidean norm (length) of this vector. Same as
+    /// [norm_fro](HMatrix::norm_fro).
+    #[inline]
+    pub fn norm(&self) -> T {
+        self.norm_fro()
+    }
+
+    /// Returns this vector scaled to unit length. The zero vector
+    /// yields NaNs.
+    #[inline]
+    pub fn normalize(&self) -> HMatrix<T, ROWS, COLS> {
+        let norm = self.norm();
+        self.map(|x| x / norm)
+    }
+
+    /// Returns the angle in radians (in `[0, π]`) between this vector and `other`,
+    /// or NaN if either of them is the zero vector.
+    #[inline]
+    pub fn angle_to<M: AsRef<[[T; COLS]; ROWS]>>(&self, other: &M) -> T {
+        angle(self.array(), other.as_ref())
+    }
+}
+
+impl<T: Numeric<T>> SVector<T, 3> {
+    /// Returns the cross product of this vector and `other`.
+    #[inline]
+    pub fn cross<M: AsRef<[[T; 1]; 3]>>(&self, other: &M) -> SVector<T, 3> {
+        let (a, b) = (self.array(), other.as_ref());
+        let c = cross([a[0][0], a[1][0], a[2][0]], [b[0][0], b[1][0], b[2][0]]);
+        SMatrix::new([[c[0]], [c[1]], [c[2]]])
+    }
+}
+
+impl<T: Numeric<T>> HVector<T, 3> {
+    /// Returns the cross product of this vector and `other`.
+    #[inline]
+    pub fn cross<M: AsRef<[[T; 1]; 3]>>(&self, other: &M) -> HVector<T, 3> {
+        let (a, b) = (self.array(), other.as_ref());
+        let c = cross([a[0][0], a[1][0], a[2][0]], [b[0][0], b[1][0], b[2][0]]);
+        HMatrix::new([[c[0]], [c[1]], [c[2]]])
+    }
+}
+
+impl<T: Numeric<T>> SRowVector<T, 3> {
+    /// Returns the cross product of this vector and `other`.
+    #[inline]
+    pub fn cross<M: AsRef<[[T; 3]; 1]>>(&self, other: &M) -> SRowVector<T, 3> {
+        SMatrix::new([cross(self[0], other.as_ref()[0])])
+    }
+}
+
+impl<T: Numeric<T>> HRowVector<T, 3> {
+    /// Returns the cross product of this vector and `other`.
+    #[inline]
+    pub fn cross<M: AsRef<[[T; 3]; 1]>>(&self, other: &M) -> HRowVector<T, 3> {
+        HMatrix::new([cross(self[0], other.as_ref()[0])])
+    }
+}
+
+impl<T: Numeric<T>, const N: usize> SVector<T, N> {
+    /// Returns the outer product `self * otherᵀ` of this column vector and
+    /// the column vector `other`.
+    #[inline]
+    pub fn outer<V: AsRef<[[T; 1]; M]>, const M: usize>(&self, other: &V) -> SMatrix<T, N, M> {
+        let b = other.as_ref();
+        SMatrix::new(from_fn(|row| outer_row(self[row][0], b)))
+    }
+}
+
+impl<T: Numeric<T>, const N: usize> HVector<T, N> {
+    /// Returns the outer product `self * otherᵀ` of this column vector and
+    /// the column vector `other`.
+    #[inline]
+    pub fn outer<V: AsRef<[[T; 1]; M]>, const M: usize>(&self, other: &V) -> HMatrix<T, N, M> {
+        let b = other.as_ref();
+        HMatrix::from_rows(self.array().iter().map(|row| outer_row(row[0], b)))
+    }
+}
+
+#[cfg(test)]
+mod matrix_vector_tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases() {
+        let m = smatrix![1, 2, 3; 4, 5, 6; 7, 8, 9];
+        let v: SVector<i32, 3> = svector![1, 0, -1];
+        let mv: SVector<i32, 3> = m * v;
+        assert_eq!(mv, svector![-2, -2, -2]);
+        let r: SRowVector<i32, 3> = rvector![1, 1, 1];
+        let rm: SRowVector<i32, 3> = r * m;
+        assert_eq!(rm, rvector![12, 15, 18]);
+        let h: HVector<i32, 3> = m.to_heap() * v;
+        assert_eq!(h, mv);
+        let hr: HRowVector<i32, 3> = r.to_heap();
+        assert_eq!(hr.trans(), svector![1, 1, 1]);
+    }
+
+    #[test]
+    fn test_dot_norm() {
+        let a = svector![1.0, 2.0, 2.0];
+        let b = svector![3.0, -1.0, 0.5];
+        assert_eq!(a.dot(&b), 2.0);
+        assert_eq!(a.to_heap().dot(&b), 2.0);
+        assert_eq!(a.norm(), 3.0);
+        assert_eq!(a.to_heap().norm(), 3.0);
+        assert_eq!(rvector![1, 2, 3].dot(&rvector![4, 5, 6]), 32);
+        // the Frobenius inner product for matrices
+        assert_eq!(smatrix![1, 2; 3, 4].dot(&hmatrix![1, 0; 0, 1]), 5);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let a = rvector![3.0f32, 0.0, -4.0];
+        assert_eq!(a.normalize(), rvector![0.6, 0.0, -0.8]);
+        assert_eq!(a.to_heap().normalize(), rvector![0.6, 0.0, -0.8]);
+        assert!((svector![1.0, 1.0, 1.0].normalize().norm() - 1.0f64).abs() < 1e-15);
+        assert!(svector![0.0, 0.0].normalize()[0][0].is_nan());
+    }
+
+    #[test]
+    fn test_angle_to() {
+        use std::f64::consts::{FRAC_PI_2, FRAC_PI_4, PI};
+        let x = svector![1.0, 0.0, 0.0];
+        let y = svector![0.0, 2.0, 0.0];
+        assert!((x.angle_to(&y) - FRAC_PI_2).abs() < 1e-15);
+        assert!((x.angle_to(&svector![1.0, 1.0, 0.0]) - FRAC_PI_4).abs() < 1e-15);
+        assert!((x.to_heap().angle_to(&(-1.0 * x)) - PI).abs() < 1e-15);
+        assert_eq!(x.angle_to(&(5.0 * x)), 0.0);
+        // acos(dot) would lose about half of the digits here
+        let tiny = svector![1.0, 1e-10, 0.0];
+        assert!((x.angle_to(&tiny) - 1e-10).abs() < 1e-24);
+        assert!(x.angle_to(&svector![0.0, 0.0, 0.0]).is_nan());
+    }
+
+    #[test]
+    fn test_angle_to_scaled() {
+        use std::f64::consts::FRAC_PI_4;
+        // the squares of the products of the elements and the norms would
+        // overflow or underflow here
+        let (a, b) = (svector![1e10f32, 0.0, 0.0], svector![1e10f32, 1e10, 0.0]);
+        assert!((a.angle_to(&b) - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        let (a, b) = (svector![1e-12f32, 0.0, 0.0], svector![1e-12f32, 1e-12, 0.0]);
+        assert!((a.to_heap().angle_to(&b) - std::f32::consts::FRAC_PI_4).abs() < 1e-6);
+        let (a, b) = (svector![1e100, 0.0, 0.0], svector![1e100, 1e100, 0.0]);
+        assert!((a.angle_to(&b) - FRAC_PI_4).abs() < 1e-15);
+        let (a, b) = (svector![1e-200, 0.0], svector![3e200, 3e200]);
+        assert!((a.angle_to(&b) - FRAC_PI_4).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_cross() {
+        let x = svector![1, 0, 0];
+        let y = svector![0, 1, 0];
+        assert_eq!(x.cross(&y), svector![0, 0, 1]);
+        assert_eq!(y.cross(&x), svector![0, 0, -1]);
+        assert_eq!(x.to_heap().cross(&y), svector![0, 0, 1]);
+        let a = rvector![1.0, 2.0, 3.0];
+        let b = rvector![-2.0, 0.5, 4.0];
+        let c = a.cross(&b.to_heap());
+        assert_eq!(c, rvector![6.5, -10.0, 4.5]);
+        assert_eq!(c.dot(&a), 0.0);
+        assert_eq!(a.to_heap().cross(&b), c);
+    }
+
+    #[test]
+    fn test_outer() {
+        let a = svector![1, 2];
+        let b = svector![3, 4, 5];
+        let expected = smatrix![3, 4, 5; 6, 8, 10];
+        let o: SMatrix<i32, 2, 3> = a.outer(&b);
+        assert_eq!(o, expected);
+        assert_eq!(o, a * b.trans());
+        let o: HMatrix<i32, 2, 3> = a.to_heap().outer(&b.to_heap());
+        assert_eq!(o, expected);
+    }
+}
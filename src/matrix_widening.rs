@@ -0,0 +1,307 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Widening multiplication and requantization for quantized integer
+//! matrices
+//!
+//! `mul_widening::<Acc>` (see [MulWidening](MulWidening)) converts every
+//! element to the wider accumulator type `Acc` (e.g. `i8` to `i32` or `i16`
+//! to `i64`) before multiplying, so neither the products nor the sums
+//! overflow for all practical inner dimensions. The zero-point variant
+//! computes `Σ (a[i][k] - za[i]) * (b[k][j] - zb[j])` with a zero point per
+//! row of the lhs and per column of the rhs, which requires a signed `Acc`
+//! (e.g. `u8` to `i32` for asymmetric quantization). `requantize` maps the
+//! accumulators back to a narrow type. As for `Mul`, the result of a
+//! stack-allocated lhs is allocated on the stack and that of a
+//! heap-allocated lhs on the heap.
+
+use crate::matrix::*;
+use std::array::from_fn;
+use std::ops::Neg;
+
+/// Integer types that quantized values and accumulators are stored in.
+pub trait QuantizedInt: Copy {
+    /// Converts `x` to `Self`, saturating at the bounds of `Self`.
+    fn from_f64_saturating(x: f64) -> Self;
+    /// Converts to `f64` (exact for all types up to 32 bits).
+    fn to_f64(self) -> f64;
+}
+
+macro_rules! impl_quantized_int {
+    ($($t:ty),*) => {
+        $(
+            impl QuantizedInt for $t {
+                #[inline]
+                fn from_f64_saturating(x: f64) -> Self {
+                    // float to int casts saturate (and map NaN to 0)
+                    x as $t
+                }
+
+                #[inline]
+                fn to_f64(self) -> f64 {
+                    self as f64
+                }
+            }
+        )*
+    };
+}
+
+impl_quantized_int!(i8, u8, i16, u16, i32, u32, i64);
+
+#[inline]
+fn multiply_widening<
+    T: Copy,
+    Acc: Numeric<Acc> + From<T>,
+    const ROWS_LEFT: usize,
+    const COLS_LEFT: usize,
+    const COLS_RIGHT: usize,
+>(
+    a: &[[T; COLS_LEFT]; ROWS_LEFT],
+    b: &[[T; COLS_RIGHT]; COLS_LEFT],
+    c: &mut [[Acc; COLS_RIGHT]; ROWS_LEFT],
+    zero_a: impl Fn(usize) -> Acc,
+    zero_b: impl Fn(usize) -> Acc,
+) {
+    for row_a in 0..ROWS_LEFT {
+        let za = zero_a(row_a);
+        for col_b in 0..COLS_RIGHT {
+            let zb = zero_b(col_b);
+            let mut sum = Acc::default();
+            for col_a in 0..COLS_LEFT {
+                sum += (Acc::from(a[row_a][col_a]) - za) * (Acc::from(b[col_a][col_b]) - zb);
+            }
+            c[row_a][col_b] = sum;
+        }
+    }
+}
+
+#[inline]
+fn requantize_row<T: QuantizedInt, Q: QuantizedInt, const COLS: usize>(
+    row: &[T; COLS],
+    scale: f64,
+    zero_point: Q,
+) -> [Q; COLS] {
+    let zero_point = zero_point.to_f64();
+    row.map(|acc| Q::from_f64_saturating((acc.to_f64() * scale).round() + zero_point))
+}
+
+/// Widening multiplication with a right-hand side of type `Rhs`, which is
+/// implemented for every combination of [SMatrix](SMatrix) and
+/// [HMatrix](HMatrix) operands of matching shapes. The shape of the
+/// product is part of the impl, so that only the accumulator type has to
+/// be named, e.g. `a.mul_widening::<i32>(&b)`.
+pub trait MulWidening<Rhs> {
+    /// The element type of both operands
+    type Elem: Copy;
+    /// The product with accumulator type `Acc`
+    type Output<Acc>;
+    /// One zero point per row of the lhs, i.e., `[Self::Elem; ROWS]`
+    type RowZeroPoints;
+    /// One zero point per column of the rhs, i.e., `[Self::Elem; COLS_RIGHT]`
+    type ColZeroPoints;
+
+    /// Multiplies with `other` accumulating in the wider type `Acc`.
+    ///
+    /// ```
+    /// use const_matrix::matrix::MulWidening;
+    /// use const_matrix::smatrix;
+    ///
+    /// let a = smatrix![100i8, 100; -128, 127];
+    /// let c = a.mul_widening::<i32>(&smatrix![127i8; 127]);
+    /// assert_eq!(c, smatrix![25_400; -127]);
+    /// ```
+    fn mul_widening<Acc: Numeric<Acc> + From<Self::Elem>>(&self, other: &Rhs) -> Self::Output<Acc>;
+
+    /// Multiplies with `other` accumulating in the wider type `Acc` after
+    /// subtracting `row_zero_points[i]` from row `i` of this matrix and
+    /// `col_zero_points[j]` from column `j` of `other`. The differences
+    /// can be negative, so `Acc` has to be signed.
+    fn mul_widening_with_zero_points<Acc: Numeric<Acc> + Neg<Output = Acc> + From<Self::Elem>>(
+        &self,
+        other: &Rhs,
+        row_zero_points: &Self::RowZeroPoints,
+        col_zero_points: &Self::ColZeroPoints,
+    ) -> Self::Output<Acc>;
+}
+
+macro_rules! impl_mul_widening {
+    ($lhs:ident, $rhs:ident, $out:ident, $new:ident) => {
+        impl<T: Copy, const ROWS: usize, const COLS: usize, const COLS_RIGHT: usize>
+            MulWidening<$rhs<T, COLS, COLS_RIGHT>> for $lhs<T, ROWS, COLS>
+        {
+            type Elem = T;
+            type Output<Acc> = $out<Acc, ROWS, COLS_RIGHT>;
+            type RowZeroPoints = [T; ROWS];
+            type ColZeroPoints = [T; COLS_RIGHT];
+
+            #[inline]
+            fn mul_widening<Acc: Numeric<Acc> + From<T>>(
+                &self,
+                other: &$rhs<T, COLS, COLS_RIGHT>,
+            ) -> $out<Acc, ROWS, COLS_RIGHT> {
+                let mut c = MF::<Acc, ROWS, COLS_RIGHT>::$new();
+                let zero = |_| Acc::default();
+                multiply_widening(self.array(), other.array(), c.array_mut(), zero, zero);
+                c
+            }
+
+            #[inline]
+            fn mul_widening_with_zero_points<Acc: Numeric<Acc> + Neg<Output = Acc> + From<T>>(
+                &self,
+                other: &$rhs<T, COLS, COLS_RIGHT>,
+                row_zero_points: &[T; ROWS],
+                col_zero_points: &[T; COLS_RIGHT],
+            ) -> $out<Acc, ROWS, COLS_RIGHT> {
+                let mut c = MF::<Acc, ROWS, COLS_RIGHT>::$new();
+                multiply_widening(
+                    self.array(),
+                    other.array(),
+                    c.array_mut(),
+                    |row| Acc::from(row_zero_points[row]),
+                    |col| Acc::from(col_zero_points[col]),
+                );
+                c
+            }
+        }
+    };
+}
+
+impl_mul_widening!(SMatrix, SMatrix, SMatrix, new_stack);
+impl_mul_widening!(SMatrix, HMatrix, SMatrix, new_stack);
+impl_mul_widening!(HMatrix, SMatrix, HMatrix, new_heap);
+impl_mul_widening!(HMatrix, HMatrix, HMatrix, new_heap);
+
+impl<T: QuantizedInt, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Maps each accumulator `acc` to `round(acc * scale) + zero_point`
+    /// saturated to `Q` (rounding half away from zero).
+    #[inline]
+    pub fn requantize<Q: QuantizedInt>(&self, scale: f64, zero_point: Q) -> SMatrix<Q, ROWS, COLS> {
+        SMatrix::new(
+            self.array()
+                .map(|row| requantize_row(&row, scale, zero_point)),
+        )
+    }
+
+    /// Same as [requantize](SMatrix::requantize) but with a separate scale
+    /// for each row (i.e., per output channel).
+    #[inline]
+    pub fn requantize_per_row<Q: QuantizedInt>(
+        &self,
+        scales: &[f64; ROWS],
+        zero_point: Q,
+    ) -> SMatrix<Q, ROWS, COLS> {
+        let a = self.array();
+        SMatrix::new(from_fn(|row| {
+            requantize_row(&a[row], scales[row], zero_point)
+        }))
+    }
+}
+
+impl<T: QuantizedInt, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Maps each accumulator `acc` to `round(acc * scale) + zero_point`
+    /// saturated to `Q` (rounding half away from zero).
+    #[inline]
+    pub fn requantize<Q: QuantizedInt>(&self, scale: f64, zero_point: Q) -> HMatrix<Q, ROWS, COLS> {
+        HMatrix::from_rows(
+            self.array()
+                .iter()
+                .map(|row| requantize_row(row, scale, zero_point)),
+        )
+    }
+
+    /// Same as [requantize](HMatrix::requantize) but with a separate scale
+    /// for each row (i.e., per output channel).
+    #[inline]
+    pub fn requantize_per_row<Q: QuantizedInt>(
+        &self,
+        scales: &[f64; ROWS],
+        zero_point: Q,
+    ) -> HMatrix<Q, ROWS, COLS> {
+        HMatrix::from_rows(
+            self.array()
+                .iter()
+                .zip(scales.iter())
+                .map(|(row, &scale)| requantize_row(row, scale, zero_point)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod matrix_widening_tests {
+    use super::*;
+
+    #[test]
+    fn test_mul_widening() {
+        let a = smatrix![127i8, 127, 127; -128, -128, -128];
+        let b = smatrix![127i8, -128; 127, -128; 127, -128];
+        let expected = smatrix![48_387, -48_768; -48_768, 49_152];
+        assert_eq!(a.mul_widening::<i32>(&b), expected);
+        assert_eq!(a.to_heap().mul_widening::<i32>(&b.to_heap()), expected);
+        let c: SMatrix<i16, 2, 1> = smatrix![10i8, 20; 30, 40].mul_widening(&svector![100i8, 100]);
+        assert_eq!(c, svector![3_000, 7_000]);
+        let d = smatrix![i16::MAX, i16::MAX].mul_widening::<i64>(&svector![i16::MAX, i16::MAX]);
+        assert_eq!(d, smatrix![2 * 32_767i64 * 32_767]);
+    }
+
+    #[test]
+    fn test_zero_points() {
+        let a = smatrix![5i16, 7; 0, 2];
+        let b = smatrix![3i16, 10; 4, 12];
+        let c = a.mul_widening_with_zero_points::<i32>(&b, &[5, 1], &[3, 10]);
+        // (a - za) = [0, 2; -1, 1], (b - zb) = [0, 0; 1, 2]
+        assert_eq!(c, smatrix![2, 4; 1, 2]);
+        let h = a
+            .to_heap()
+            .mul_widening_with_zero_points::<i64>(&b, &[5, 1], &[3, 10]);
+        assert_eq!(h, smatrix![2i64, 4; 1, 2]);
+        let zero = a.mul_widening_with_zero_points::<i32>(&b, &[0, 0], &[0, 0]);
+        assert_eq!(zero, a.mul_widening::<i32>(&b));
+    }
+
+    #[test]
+    fn test_asymmetric_u8() {
+        // u8 values with zero point 128 represent -128..=127
+        let a = smatrix![128u8, 255; 0, 130];
+        let b = hmatrix![1u8, 128; 255, 0];
+        let c = a.mul_widening_with_zero_points::<i32>(&b, &[128, 128], &[128, 128]);
+        // (a - 128) = [0, 127; -128, 2], (b - 128) = [-127, 0; 127, -128]
+        assert_eq!(c, smatrix![16_129, -16_256; 16_510, -256]);
+        let h = a
+            .to_heap()
+            .mul_widening_with_zero_points::<i64>(&b, &[0, 128], &[1, 0]);
+        // (a - za) = [128, 255; -128, 2], (b - zb) = [0, 128; 254, 0]
+        assert_eq!(h, smatrix![64_770i64, 16_384; 508, -16_384]);
+        // without zero points an unsigned accumulator is fine
+        assert_eq!(
+            a.mul_widening::<u32>(&b),
+            smatrix![65_153u32, 16_384; 33_150, 0]
+        );
+    }
+
+    #[test]
+    fn test_requantize() {
+        let acc = smatrix![1_000i32, -1_000, 25; 100_000, -100_000, -25];
+        let q: SMatrix<i8, 2, 3> = acc.requantize(0.1, 0);
+        assert_eq!(q, smatrix![100, -100, 3; 127, -128, -3]);
+        let q: HMatrix<u8, 2, 3> = acc.to_heap().requantize(0.1, 128u8);
+        assert_eq!(q, smatrix![228u8, 28, 131; 255, 0, 125]);
+        let q = acc.requantize_per_row(&[0.01, 0.001], 0i8);
+        assert_eq!(q, smatrix![10, -10, 0; 100, -100, 0]);
+        let q = acc.to_heap().requantize_per_row(&[1.0, 0.0], 5i16);
+        assert_eq!(q, smatrix![1_005i16, -995, 30; 5, 5, 5]);
+    }
+
+    #[test]
+    fn test_quantized_pipeline() {
+        // int8 weights times int8 activations, requantized to int8
+        let weights = smatrix![64i8, -32; 16, 127];
+        let activations = svector![100i8, -50];
+        let acc = weights.mul_widening::<i32>(&activations);
+        assert_eq!(acc, svector![8_000, -4_750]);
+        assert_eq!(acc.requantize(1.0 / 64.0, 0i8), svector![125, -74]);
+    }
+}
@@ -0,0 +1,495 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Reading and writing NumPy `.npy` files
+//!
+//! `to_npy` writes a `ROWS x COLS` array in C order and little-endian byte
+//! order, which `numpy.load` reads back as an array of shape `(ROWS, COLS)`.
+//! `from_npy` reads format versions 1.0 to 3.0 in C or Fortran order and
+//! either byte order, but fails with an [NpyError](NpyError) unless the
+//! dtype matches the element type and the shape is exactly `(ROWS, COLS)`.
+//! The element types are all primitive integers and floats except `i128`
+//! and `u128`, which have no NumPy dtype.
+//!
+//! ```
+//! use const_matrix::matrix::SMatrix;
+//! use const_matrix::smatrix;
+//!
+//! let m = smatrix![1.0, 2.0, 3.0; 4.0, 5.0, 6.0];
+//! let mut file = Vec::new();
+//! m.to_npy(&mut file).unwrap();
+//! let back = SMatrix::<f64, 2, 3>::from_npy(file.as_slice()).unwrap();
+//! assert_eq!(back, m);
+//! assert!(SMatrix::<f32, 2, 3>::from_npy(file.as_slice()).is_err());
+//! ```
+
+use crate::matrix::*;
+use std::convert::TryInto;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::io::{self, Read, Write};
+
+const MAGIC: &[u8; 6] = b"\x93NUMPY";
+/// The longest header that is read, the same limit as numpy's default
+const MAX_HEADER_LEN: usize = 10_000;
+
+/// An element type with a NumPy dtype.
+pub trait NpyElement: Copy {
+    /// The kind and size of the dtype without the byte order, e.g. `"f8"`
+    const DTYPE: &'static str;
+
+    /// Appends the little-endian bytes of `self` to `out`.
+    fn write_le(self, out: &mut Vec<u8>);
+
+    /// Creates a value from `bytes` in little- or big-endian byte order.
+    fn from_bytes(bytes: &[u8], little_endian: bool) -> Self;
+}
+
+macro_rules! impl_npy_element {
+    ($($t:ty => $dtype:literal),*) => {
+        $(
+        impl NpyElement for $t {
+            const DTYPE: &'static str = $dtype;
+
+            #[inline]
+            fn write_le(self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            #[inline]
+            fn from_bytes(bytes: &[u8], little_endian: bool) -> Self {
+                let bytes = bytes.try_into().unwrap();
+                if little_endian {
+                    <$t>::from_le_bytes(bytes)
+                } else {
+                    <$t>::from_be_bytes(bytes)
+                }
+            }
+        }
+        )*
+    };
+}
+
+impl_npy_element!(
+    i8 => "i1", i16 => "i2", i32 => "i4", i64 => "i8",
+    u8 => "u1", u16 => "u2", u32 => "u4", u64 => "u8",
+    f32 => "f4", f64 => "f8"
+);
+
+#[cfg(target_pointer_width = "64")]
+impl_npy_element!(isize => "i8", usize => "u8");
+
+#[cfg(target_pointer_width = "32")]
+impl_npy_element!(isize => "i4", usize => "u4");
+
+/// An error which can be returned when reading a `.npy` file.
+#[derive(Debug)]
+pub enum NpyError {
+    /// Reading failed, e.g. because the data ended prematurely.
+    Io(io::Error),
+    /// The input doesn't start with the `.npy` magic string.
+    InvalidMagic,
+    /// The format version is not 1.0, 2.0 or 3.0.
+    UnsupportedVersion {
+        /// The major version
+        major: u8,
+        /// The minor version
+        minor: u8,
+    },
+    /// The header dictionary is malformed or lacks a key.
+    InvalidHeader(String),
+    /// The dtype of the file doesn't match the element type.
+    DtypeMismatch {
+        /// The dtype of the element type (without the byte order)
+        expected: &'static str,
+        /// The dtype descriptor of the file
+        found: String,
+    },
+    /// The shape of the file isn't `(ROWS, COLS)`.
+    ShapeMismatch {
+        /// The `(ROWS, COLS)` of the matrix type
+        expected: (usize, usize),
+        /// The shape of the file
+        found: Vec<usize>,
+    },
+}
+
+impl Display for NpyError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            NpyError::Io(e) => write!(f, "reading the .npy data failed: {}", e),
+            NpyError::InvalidMagic => write!(f, "not a .npy file"),
+            NpyError::UnsupportedVersion { major, minor } => {
+                write!(f, "unsupported .npy format version {}.{}", major, minor)
+            }
+            NpyError::InvalidHeader(message) => write!(f, "invalid .npy header: {}", message),
+            NpyError::DtypeMismatch { expected, found } => {
+                write!(f, "expected dtype {} but found '{}'", expected, found)
+            }
+            NpyError::ShapeMismatch { expected, found } => write!(
+                f,
+                "expected shape ({}, {}) but found {:?}",
+                expected.0, expected.1, found
+            ),
+        }
+    }
+}
+
+impl Error for NpyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NpyError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NpyError {
+    fn from(e: io::Error) -> Self {
+        NpyError::Io(e)
+    }
+}
+
+fn write_npy<T: NpyElement, W: Write, const ROWS: usize, const COLS: usize>(
+    a: &[[T; COLS]; ROWS],
+    mut writer: W,
+) -> io::Result<()> {
+    let mut header = format!(
+        "{{'descr': '{}{}', 'fortran_order': False, 'shape': ({}, {}), }}",
+        if T::DTYPE.ends_with('1') { '|' } else { '<' },
+        T::DTYPE,
+        ROWS,
+        COLS
+    );
+    // the data starts at a multiple of 64 bytes after the preamble (magic,
+    // version and header length) and the newline-terminated header, which
+    // is always short enough for version 1.0
+    let preamble = MAGIC.len() + 4;
+    let padding = (64 - (preamble + header.len() + 1) % 64) % 64;
+    header.extend(std::iter::repeat_n(' ', padding));
+    header.push('\n');
+    // a single write, so that an unbuffered writer isn't called per element
+    let data_len = ROWS * COLS * std::mem::size_of::<T>();
+    let mut file = Vec::with_capacity(preamble + header.len() + data_len);
+    file.extend_from_slice(MAGIC);
+    file.extend_from_slice(&[1, 0]);
+    file.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    file.extend_from_slice(header.as_bytes());
+    for &x in a.iter().flat_map(|row| row.iter()) {
+        x.write_le(&mut file);
+    }
+    writer.write_all(&file)?;
+    writer.flush()
+}
+
+// Returns the text after `key:` in the header dictionary
+fn header_value<'a>(header: &'a str, key: &str) -> Result<&'a str, NpyError> {
+    let pos = header
+        .find(&format!("'{}'", key))
+        .or_else(|| header.find(&format!("\"{}\"", key)))
+        .ok_or_else(|| NpyError::InvalidHeader(format!("missing key '{}'", key)))?;
+    let rest = header[pos + key.len() + 2..].trim_start();
+    rest.strip_prefix(':')
+        .map(str::trim_start)
+        .ok_or_else(|| NpyError::InvalidHeader(format!("expected ':' after '{}'", key)))
+}
+
+fn parse_descr(header: &str) -> Result<&str, NpyError> {
+    let value = header_value(header, "descr")?;
+    let quote = value.chars().next().filter(|&c| c == '\'' || c == '"');
+    let invalid = || NpyError::InvalidHeader("'descr' is not a string".to_string());
+    let quote = quote.ok_or_else(invalid)?;
+    let end = value[1..].find(quote).ok_or_else(invalid)?;
+    Ok(&value[1..end + 1])
+}
+
+fn parse_fortran_order(header: &str) -> Result<bool, NpyError> {
+    let value = header_value(header, "fortran_order")?;
+    if value.starts_with("True") {
+        Ok(true)
+    } else if value.starts_with("False") {
+        Ok(false)
+    } else {
+        Err(NpyError::InvalidHeader(
+            "'fortran_order' is not a boolean".to_string(),
+        ))
+    }
+}
+
+fn parse_shape(header: &str) -> Result<Vec<usize>, NpyError> {
+    let value = header_value(header, "shape")?;
+    let invalid = || NpyError::InvalidHeader("'shape' is not a tuple of integers".to_string());
+    let end = value.find(')').ok_or_else(invalid)?;
+    let dims = value.strip_prefix('(').ok_or_else(invalid)?;
+    dims[..end - 1]
+        .split(',')
+        .map(str::trim)
+        .filter(|dim| !dim.is_empty())
+        .map(|dim| dim.trim_end_matches('L').parse().map_err(|_| invalid()))
+        .collect()
+}
+
+// Checks the dtype and returns whether the data is little-endian
+fn check_descr<T: NpyElement>(descr: &str) -> Result<bool, NpyError> {
+    let mismatch = || NpyError::DtypeMismatch {
+        expected: T::DTYPE,
+        found: descr.to_string(),
+    };
+    let mut chars = descr.chars();
+    let little_endian = match chars.next() {
+        Some('<') => true,
+        Some('>') => false,
+        Some('=') => cfg!(target_endian = "little"),
+        Some('|') if T::DTYPE.ends_with('1') => true,
+        _ => return Err(mismatch()),
+    };
+    if chars.as_str() == T::DTYPE {
+        Ok(little_endian)
+    } else {
+        Err(mismatch())
+    }
+}
+
+fn read_npy<T: NpyElement, R: Read, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    mut reader: R,
+) -> Result<(), NpyError> {
+    let mut magic = [0u8; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic[..6] != MAGIC {
+        return Err(NpyError::InvalidMagic);
+    }
+    let header_len = match (magic[6], magic[7]) {
+        (1, 0) => {
+            let mut len = [0u8; 2];
+            reader.read_exact(&mut len)?;
+            u16::from_le_bytes(len) as usize
+        }
+        (2, 0) | (3, 0) => {
+            let mut len = [0u8; 4];
+            reader.read_exact(&mut len)?;
+            u32::from_le_bytes(len) as usize
+        }
+        (major, minor) => return Err(NpyError::UnsupportedVersion { major, minor }),
+    };
+    // don't trust the declared length before any of the header is read
+    if header_len > MAX_HEADER_LEN {
+        return Err(NpyError::InvalidHeader(format!(
+            "the header length {} exceeds {} bytes",
+            header_len, MAX_HEADER_LEN
+        )));
+    }
+    let mut header = Vec::with_capacity(header_len);
+    reader
+        .by_ref()
+        .take(header_len as u64)
+        .read_to_end(&mut header)?;
+    if header.len() < header_len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    // versions 1.0 and 2.0 are Latin-1 but valid headers only contain ASCII
+    let header = String::from_utf8(header)
+        .map_err(|_| NpyError::InvalidHeader("the header is not UTF-8".to_string()))?;
+
+    let little_endian = check_descr::<T>(parse_descr(&header)?)?;
+    let fortran_order = parse_fortran_order(&header)?;
+    let shape = parse_shape(&header)?;
+    if shape != [ROWS, COLS] {
+        return Err(NpyError::ShapeMismatch {
+            expected: (ROWS, COLS),
+            found: shape,
+        });
+    }
+
+    let size = std::mem::size_of::<T>();
+    let mut data = vec![0u8; ROWS * COLS * size];
+    reader.read_exact(&mut data)?;
+    for (k, bytes) in data.chunks_exact(size).enumerate() {
+        let (i, j) = if fortran_order {
+            (k % ROWS, k / ROWS)
+        } else {
+            (k / COLS, k % COLS)
+        };
+        a[i][j] = T::from_bytes(bytes, little_endian);
+    }
+    Ok(())
+}
+
+impl<T: Numeric<T> + NpyElement, const ROWS: usize, const COLS: usize> SMatrix<T, ROWS, COLS> {
+    /// Writes this matrix in the `.npy` format to `writer`.
+    #[inline]
+    pub fn to_npy<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_npy(self.array(), writer)
+    }
+
+    /// Reads a matrix in the `.npy` format from `reader`.
+    pub fn from_npy<R: Read>(reader: R) -> Result<Self, NpyError> {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        read_npy(m.array_mut(), reader)?;
+        Ok(m)
+    }
+}
+
+impl<T: Numeric<T> + NpyElement, const ROWS: usize, const COLS: usize> HMatrix<T, ROWS, COLS> {
+    /// Writes this matrix in the `.npy` format to `writer`.
+    #[inline]
+    pub fn to_npy<W: Write>(&self, writer: W) -> io::Result<()> {
+        write_npy(self.array(), writer)
+    }
+
+    /// Reads a matrix in the `.npy` format from `reader`.
+    pub fn from_npy<R: Read>(reader: R) -> Result<Self, NpyError> {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        read_npy(m.array_mut(), reader)?;
+        Ok(m)
+    }
+}
+
+#[cfg(test)]
+mod npy_tests {
+    use super::*;
+
+    fn npy_file(version: u8, dict: &str, data: &[u8]) -> Vec<u8> {
+        let mut file = MAGIC.to_vec();
+        file.extend([version, 0]);
+        if version == 1 {
+            file.extend((dict.len() as u16).to_le_bytes());
+        } else {
+            file.extend((dict.len() as u32).to_le_bytes());
+        }
+        file.extend(dict.as_bytes());
+        file.extend(data);
+        file
+    }
+
+    #[test]
+    fn test_numpy_layout() {
+        // the bytes written by numpy.save(f, numpy.array([[1, 2], [3, 4]], dtype='<i4'))
+        let dict = "{'descr': '<i4', 'fortran_order': False, 'shape': (2, 2), }";
+        let header = format!("{:<117}\n", dict);
+        let data: Vec<u8> = [1i32, 2, 3, 4]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let expected = npy_file(1, &header, &data);
+        assert_eq!(expected.len(), 128 + 16);
+        let mut file = Vec::new();
+        smatrix![1i32, 2; 3, 4].to_npy(&mut file).unwrap();
+        assert_eq!(file, expected);
+        let mut file = Vec::new();
+        hmatrix![1u8, 2; 3, 4].to_npy(&mut file).unwrap();
+        assert!(file.starts_with(b"\x93NUMPY\x01\x00v\x00{'descr': '|u1',"));
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let m = smatrix![1.5f32, -2.0, 3.25; 4.0, 5.0, f32::MAX];
+        let mut file = Vec::new();
+        m.to_npy(&mut file).unwrap();
+        assert_eq!(file.len(), 128 + 6 * 4);
+        assert_eq!(HMatrix::<f32, 2, 3>::from_npy(file.as_slice()).unwrap(), m);
+        let h = hmatrix![i64::MIN, 0, i64::MAX];
+        let mut file = Vec::new();
+        h.to_npy(&mut file).unwrap();
+        assert_eq!(SMatrix::<i64, 1, 3>::from_npy(&file[..]).unwrap(), h);
+    }
+
+    #[test]
+    fn test_single_write() {
+        struct CountingWriter(Vec<u8>, usize);
+
+        impl Write for CountingWriter {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                self.1 += 1;
+                self.0.write(buf)
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let m = MF::<u16, 8, 8>::unit_heap();
+        let mut writer = CountingWriter(Vec::new(), 0);
+        m.to_npy(&mut writer).unwrap();
+        assert_eq!(writer.1, 1);
+        assert_eq!(SMatrix::<u16, 8, 8>::from_npy(&writer.0[..]).unwrap(), m);
+    }
+
+    #[test]
+    fn test_fortran_order_and_big_endian() {
+        // column-major data of [[1, 2, 3], [4, 5, 6]]
+        let data: Vec<u8> = [1u16, 4, 2, 5, 3, 6]
+            .iter()
+            .flat_map(|x| x.to_be_bytes())
+            .collect();
+        let dict = "{\"descr\": \">u2\", \"fortran_order\": True, \"shape\": (2, 3)}\n";
+        let file = npy_file(2, dict, &data);
+        let m = SMatrix::<u16, 2, 3>::from_npy(file.as_slice()).unwrap();
+        assert_eq!(m, smatrix![1, 2, 3; 4, 5, 6]);
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut file = Vec::new();
+        smatrix![1.0, 2.0; 3.0, 4.0].to_npy(&mut file).unwrap();
+        assert!(matches!(
+            SMatrix::<f64, 2, 2>::from_npy(&file[1..]),
+            Err(NpyError::InvalidMagic)
+        ));
+        match SMatrix::<i64, 2, 2>::from_npy(file.as_slice()) {
+            Err(NpyError::DtypeMismatch { expected, found }) => {
+                assert_eq!((expected, found.as_str()), ("i8", "<f8"));
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        match HMatrix::<f64, 4, 1>::from_npy(file.as_slice()) {
+            Err(e @ NpyError::ShapeMismatch { .. }) => {
+                assert_eq!(e.to_string(), "expected shape (4, 1) but found [2, 2]");
+            }
+            r => panic!("unexpected result {:?}", r),
+        }
+        assert!(matches!(
+            SMatrix::<f64, 2, 2>::from_npy(&file[..file.len() - 1]),
+            Err(NpyError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+        let mut bad_version = file.clone();
+        bad_version[6] = 4;
+        assert!(matches!(
+            SMatrix::<f64, 2, 2>::from_npy(bad_version.as_slice()),
+            Err(NpyError::UnsupportedVersion { major: 4, minor: 0 })
+        ));
+        let vector = npy_file(
+            1,
+            "{'descr': '<f8', 'fortran_order': False, 'shape': (4,), }\n",
+            &[],
+        );
+        assert!(matches!(
+            SMatrix::<f64, 2, 2>::from_npy(vector.as_slice()),
+            Err(NpyError::ShapeMismatch { found, .. }) if found == [4]
+        ));
+        let no_shape = npy_file(1, "{'descr': '<f8', 'fortran_order': False}\n", &[]);
+        assert!(matches!(
+            SMatrix::<f64, 2, 2>::from_npy(no_shape.as_slice()),
+            Err(NpyError::InvalidHeader(_))
+        ));
+        // a huge declared header length is rejected before it is read
+        let huge: &[u8] = b"\x93NUMPY\x02\x00\xf0\xff\xff\x0f";
+        match HMatrix::<f64, 2, 2>::from_npy(huge) {
+            Err(e @ NpyError::InvalidHeader(_)) => assert_eq!(
+                e.to_string(),
+                "invalid .npy header: the header length 268435440 exceeds 10000 bytes"
+            ),
+            r => panic!("unexpected result {:?}", r),
+        }
+        let truncated: &[u8] = b"\x93NUMPY\x01\x00\x10\x00{'descr'";
+        assert!(matches!(
+            SMatrix::<f64, 2, 2>::from_npy(truncated),
+            Err(NpyError::Io(e)) if e.kind() == io::ErrorKind::UnexpectedEof
+        ));
+    }
+}
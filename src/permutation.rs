@@ -0,0 +1,199 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Permutations of `N` elements
+//!
+//! A [Permutation](Permutation) `p` maps `i` to `p[i]`. Applied to a matrix,
+//! `m.permute_rows(&p)` moves row `p[i]` of `m` to row `i` which is the
+//! same as `p.to_matrix() * m`, and `m.permute_cols(&p)` moves column
+//! `p[j]` to column `j` which is the same as `m * p.to_matrix().trans()`,
+//! only without any multiplications.
+
+use crate::matrix::*;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::ops::Index;
+
+/// A permutation of `0..N` which is guaranteed to contain every index
+/// exactly once.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Permutation<const N: usize> {
+    p: [usize; N],
+}
+
+/// An error which is returned when an array isn't a permutation of `0..N`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidPermutationError {
+    /// The position of the first entry that is out of range or repeated
+    pub position: usize,
+}
+
+impl Display for InvalidPermutationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "entry {} of the permutation is out of range or repeated",
+            self.position
+        )
+    }
+}
+
+impl Error for InvalidPermutationError {}
+
+impl<const N: usize> Permutation<N> {
+    /// Returns the identity permutation.
+    #[inline]
+    pub fn identity() -> Self {
+        Permutation {
+            p: std::array::from_fn(|i| i),
+        }
+    }
+
+    /// Creates a permutation from `p` if `p` contains every index of
+    /// `0..N` exactly once.
+    pub fn new(p: [usize; N]) -> Result<Self, InvalidPermutationError> {
+        let mut seen = [false; N];
+        for (position, &i) in p.iter().enumerate() {
+            if i >= N || seen[i] {
+                return Err(InvalidPermutationError { position });
+            }
+            seen[i] = true;
+        }
+        Ok(Permutation { p })
+    }
+
+    /// Returns the underlying array.
+    #[inline]
+    pub fn as_array(&self) -> &[usize; N] {
+        &self.p
+    }
+
+    /// Swaps the entries `i` and `j`, i.e., composes this permutation with
+    /// the transposition of `i` and `j` (e.g., to record a row swap while
+    /// pivoting).
+    #[inline]
+    pub fn swap(&mut self, i: usize, j: usize) {
+        self.p.swap(i, j);
+    }
+
+    /// Returns `self ∘ other`, i.e., the permutation that maps `i` to
+    /// `self[other[i]]`. Permuting by the composition is the same as
+    /// permuting by `self` first and then by `other`.
+    #[inline]
+    pub fn compose(&self, other: &Self) -> Self {
+        Permutation {
+            p: other.p.map(|i| self.p[i]),
+        }
+    }
+
+    /// Returns the inverse permutation.
+    #[inline]
+    pub fn inverse(&self) -> Self {
+        let mut inv = [0; N];
+        for (i, &j) in self.p.iter().enumerate() {
+            inv[j] = i;
+        }
+        Permutation { p: inv }
+    }
+
+    /// Returns the sign (`1` for even and `-1` for odd permutations), which
+    /// is also the determinant of [to_matrix](Permutation::to_matrix).
+    pub fn sign(&self) -> i32 {
+        let mut visited = [false; N];
+        let mut cycles = 0;
+        for start in 0..N {
+            if !visited[start] {
+                cycles += 1;
+                let mut i = start;
+                while !visited[i] {
+                    visited[i] = true;
+                    i = self.p[i];
+                }
+            }
+        }
+        if (N - cycles) % 2 == 0 {
+            1
+        } else {
+            -1
+        }
+    }
+
+    /// Returns the permutation matrix `P` with `P[i][p[i]] = 1`.
+    #[inline]
+    pub fn to_matrix<T: Numeric<T> + One>(&self) -> SMatrix<T, N, N> {
+        let mut m = MF::<T, N, N>::new_stack();
+        for (i, &j) in self.p.iter().enumerate() {
+            m[i][j] = T::one();
+        }
+        m
+    }
+}
+
+impl<const N: usize> Default for Permutation<N> {
+    #[inline]
+    fn default() -> Self {
+        Permutation::identity()
+    }
+}
+
+impl<const N: usize> Index<usize> for Permutation<N> {
+    type Output = usize;
+
+    #[inline]
+    fn index(&self, i: usize) -> &usize {
+        &self.p[i]
+    }
+}
+
+#[cfg(test)]
+mod permutation_tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert!(Permutation::new([2, 0, 1]).is_ok());
+        assert_eq!(
+            Permutation::new([0, 3, 1]),
+            Err(InvalidPermutationError { position: 1 })
+        );
+        assert_eq!(
+            Permutation::new([1, 0, 1]),
+            Err(InvalidPermutationError { position: 2 })
+        );
+        assert_eq!(
+            Permutation::<4>::default(),
+            Permutation::new([0, 1, 2, 3]).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_compose_inverse() {
+        let a = Permutation::new([2, 0, 1, 3]).unwrap();
+        let b = Permutation::new([1, 0, 3, 2]).unwrap();
+        assert_eq!(a.compose(&b).as_array(), &[0, 2, 3, 1]);
+        assert_eq!(a.compose(&a.inverse()), Permutation::identity());
+        assert_eq!(a.inverse().compose(&a), Permutation::identity());
+        let pa = a.to_matrix::<i32>();
+        let pb = b.to_matrix::<i32>();
+        assert_eq!(a.compose(&b).to_matrix::<i32>(), pb * pa);
+        assert_eq!(a.inverse().to_matrix::<i32>(), pa.trans());
+    }
+
+    #[test]
+    fn test_sign() {
+        assert_eq!(Permutation::<5>::identity().sign(), 1);
+        let mut p = Permutation::<5>::identity();
+        p.swap(1, 3);
+        assert_eq!(p.sign(), -1);
+        p.swap(0, 4);
+        assert_eq!(p.sign(), 1);
+        // a 3-cycle is even, a 4-cycle is odd
+        assert_eq!(Permutation::new([1, 2, 0]).unwrap().sign(), 1);
+        assert_eq!(Permutation::new([1, 2, 3, 0]).unwrap().sign(), -1);
+        assert_eq!(Permutation::<0>::identity().sign(), 1);
+    }
+}
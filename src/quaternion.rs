@@ -0,0 +1,470 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Quaternions and unit quaternions for 3D rotations
+//!
+//! A [Quaternion](Quaternion) is [Numeric](crate::matrix::Numeric) (with the
+//! Hamilton product as `*`), so it can also be the element type of a
+//! matrix. A [UnitQuaternion](UnitQuaternion) is a quaternion of norm 1
+//! which represents a rotation and converts to and from `3 x 3` rotation
+//! matrices. Rotations follow the conventions of the
+//! [geometry](crate::geometry) module.
+
+use crate::matrix::*;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// A quaternion `w + xi + yj + zk`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Quaternion<T> {
+    /// The real (scalar) part
+    pub w: T,
+    /// The coefficient of `i`
+    pub x: T,
+    /// The coefficient of `j`
+    pub y: T,
+    /// The coefficient of `k`
+    pub z: T,
+}
+
+/// A quaternion of norm 1 which represents a rotation in 3D.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct UnitQuaternion<T> {
+    q: Quaternion<T>,
+}
+
+impl<T> Quaternion<T> {
+    /// Creates the quaternion `w + xi + yj + zk`.
+    #[inline]
+    pub const fn new(w: T, x: T, y: T, z: T) -> Self {
+        Quaternion { w, x, y, z }
+    }
+}
+
+impl<T: Numeric<T>> Quaternion<T> {
+    /// Returns the conjugate `w - xi - yj - zk`.
+    #[inline]
+    pub fn conjugate(&self) -> Self
+    where
+        T: Neg<Output = T>,
+    {
+        Quaternion::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Returns the dot product of the coefficients of both quaternions.
+    #[inline]
+    pub fn dot(&self, other: &Self) -> T {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    /// Returns the squared norm `w² + x² + y² + z²`.
+    #[inline]
+    pub fn norm_squared(&self) -> T {
+        self.dot(self)
+    }
+
+    #[inline]
+    fn scale(&self, s: T) -> Self {
+        Quaternion::new(self.w * s, self.x * s, self.y * s, self.z * s)
+    }
+}
+
+impl<T: Real> Quaternion<T> {
+    /// Returns the norm (length) of this quaternion.
+    #[inline]
+    pub fn norm(&self) -> T {
+        SMatrix::new([[self.w, self.x, self.y, self.z]]).norm_fro()
+    }
+
+    /// Returns this quaternion scaled to norm 1. The zero quaternion
+    /// yields NaNs.
+    #[inline]
+    pub fn normalize(&self) -> Self {
+        self.scale(T::one() / self.norm())
+    }
+
+    /// Spherical linear interpolation between this quaternion (`t = 0`)
+    /// and `other` (`t = 1`) along the shorter arc. Both are expected to
+    /// be unit quaternions and so is the result.
+    pub fn slerp(&self, other: &Self, t: T) -> Self {
+        let mut dot = self.dot(other);
+        let mut other = *other;
+        // q and -q represent the same rotation
+        if dot < T::default() {
+            other = -other;
+            dot = -dot;
+        }
+        if dot > T::one() - T::from_f64(1e-6) {
+            // nearly parallel: the linear interpolation is accurate
+            return (*self + (other - *self).scale(t)).normalize();
+        }
+        let theta = dot.min(T::one()).acos();
+        let sin_theta = theta.sin();
+        let a = ((T::one() - t) * theta).sin() / sin_theta;
+        let b = (t * theta).sin() / sin_theta;
+        self.scale(a) + other.scale(b)
+    }
+
+    /// Returns the rotation matrix of this quaternion. Quaternions that
+    /// aren't normalized are treated as if they were.
+    pub fn to_rotation_matrix(&self) -> SMatrix<T, 3, 3> {
+        let Quaternion { w, x, y, z } = *self;
+        let s = T::from_f64(2.0) / self.norm_squared();
+        let one = T::one();
+        SMatrix::new([
+            [
+                one - s * (y * y + z * z),
+                s * (x * y - w * z),
+                s * (x * z + w * y),
+            ],
+            [
+                s * (x * y + w * z),
+                one - s * (x * x + z * z),
+                s * (y * z - w * x),
+            ],
+            [
+                s * (x * z - w * y),
+                s * (y * z + w * x),
+                one - s * (x * x + y * y),
+            ],
+        ])
+    }
+
+    /// Returns the unit quaternion (with `w >= 0`) of the rotation matrix
+    /// `r`. Uses Shepperd's method which divides by the largest of the four
+    /// possible pivots and is therefore accurate for all rotations.
+    pub fn from_rotation_matrix<M: AsRef<[[T; 3]; 3]>>(r: &M) -> Self {
+        let r = r.as_ref();
+        let (one, quarter) = (T::one(), T::from_f64(0.25));
+        let half = T::from_f64(0.5);
+        let trace = r[0][0] + r[1][1] + r[2][2];
+        let q = if trace >= r[0][0] && trace >= r[1][1] && trace >= r[2][2] {
+            let w = half * (one + trace).sqrt();
+            let f = quarter / w;
+            Quaternion::new(
+                w,
+                (r[2][1] - r[1][2]) * f,
+                (r[0][2] - r[2][0]) * f,
+                (r[1][0] - r[0][1]) * f,
+            )
+        } else if r[0][0] >= r[1][1] && r[0][0] >= r[2][2] {
+            let x = half * (one + r[0][0] + r[0][0] - trace).sqrt();
+            let f = quarter / x;
+            Quaternion::new(
+                (r[2][1] - r[1][2]) * f,
+                x,
+                (r[0][1] + r[1][0]) * f,
+                (r[0][2] + r[2][0]) * f,
+            )
+        } else if r[1][1] >= r[2][2] {
+            let y = half * (one + r[1][1] + r[1][1] - trace).sqrt();
+            let f = quarter / y;
+            Quaternion::new(
+                (r[0][2] - r[2][0]) * f,
+                (r[0][1] + r[1][0]) * f,
+                y,
+                (r[1][2] + r[2][1]) * f,
+            )
+        } else {
+            let z = half * (one + r[2][2] + r[2][2] - trace).sqrt();
+            let f = quarter / z;
+            Quaternion::new(
+                (r[1][0] - r[0][1]) * f,
+                (r[0][2] + r[2][0]) * f,
+                (r[1][2] + r[2][1]) * f,
+                z,
+            )
+        };
+        if q.w < T::default() {
+            -q
+        } else {
+            q
+        }
+    }
+}
+
+impl<T: Numeric<T> + One> One for Quaternion<T> {
+    #[inline]
+    fn one() -> Self {
+        Quaternion::new(T::one(), T::default(), T::default(), T::default())
+    }
+}
+
+impl<T: Real> UnitQuaternion<T> {
+    /// Returns the identity rotation.
+    #[inline]
+    pub fn identity() -> Self {
+        UnitQuaternion {
+            q: Quaternion::one(),
+        }
+    }
+
+    /// Normalizes `q` to a unit quaternion.
+    #[inline]
+    pub fn new_normalize(q: Quaternion<T>) -> Self {
+        UnitQuaternion { q: q.normalize() }
+    }
+
+    /// Returns the rotation by `angle` (in radians) about `axis` which
+    /// doesn't need to be normalized.
+    pub fn from_axis_angle<M: AsRef<[[T; 1]; 3]>>(axis: &M, angle: T) -> Self {
+        let k = SMatrix::new(*axis.as_ref()).normalize();
+        let half = angle * T::from_f64(0.5);
+        let s = half.sin();
+        UnitQuaternion {
+            q: Quaternion::new(half.cos(), k[0][0] * s, k[1][0] * s, k[2][0] * s),
+        }
+    }
+
+    /// Returns the rotation of the rotation matrix `r` (see
+    /// [Quaternion::from_rotation_matrix](Quaternion::from_rotation_matrix)).
+    #[inline]
+    pub fn from_rotation_matrix<M: AsRef<[[T; 3]; 3]>>(r: &M) -> Self {
+        UnitQuaternion {
+            q: Quaternion::from_rotation_matrix(r),
+        }
+    }
+
+    /// Returns the rotation matrix of this rotation.
+    #[inline]
+    pub fn to_rotation_matrix(&self) -> SMatrix<T, 3, 3> {
+        self.q.to_rotation_matrix()
+    }
+
+    /// Returns the underlying quaternion.
+    #[inline]
+    pub fn quaternion(&self) -> Quaternion<T> {
+        self.q
+    }
+
+    /// Returns the inverse rotation, i.e., the conjugate.
+    #[inline]
+    pub fn inverse(&self) -> Self {
+        UnitQuaternion {
+            q: self.q.conjugate(),
+        }
+    }
+
+    /// Returns the angle of this rotation in `[0, π]`.
+    #[inline]
+    pub fn angle(&self) -> T {
+        let v = SMatrix::new([[self.q.x, self.q.y, self.q.z]]).norm_fro();
+        T::from_f64(2.0) * v.atan2(self.q.w.abs())
+    }
+
+    /// Spherical linear interpolation between this rotation (`t = 0`)
+    /// and `other` (`t = 1`).
+    #[inline]
+    pub fn slerp(&self, other: &Self, t: T) -> Self {
+        UnitQuaternion::new_normalize(self.q.slerp(&other.q, t))
+    }
+
+    /// Rotates the vector `v`.
+    pub fn rotate_vector<M: AsRef<[[T; 1]; 3]>>(&self, v: &M) -> SVector<T, 3> {
+        // v' = v + w t + u × t with t = 2 u × v and u = (x, y, z)
+        let v = SMatrix::new(*v.as_ref());
+        let u = svector![self.q.x, self.q.y, self.q.z];
+        let t = T::from_f64(2.0);
+        let t = u.cross(&v).map(|c| c * t);
+        let w = self.q.w;
+        v + t.map(|c| c * w) + u.cross(&t)
+    }
+}
+
+impl<T: Real> Mul for UnitQuaternion<T> {
+    type Output = UnitQuaternion<T>;
+
+    /// The composition of both rotations (`rhs` is applied first).
+    #[inline]
+    fn mul(self, rhs: UnitQuaternion<T>) -> Self::Output {
+        UnitQuaternion { q: self.q * rhs.q }
+    }
+}
+
+impl<T: Numeric<T>> Add for Quaternion<T> {
+    type Output = Quaternion<T>;
+
+    #[inline]
+    fn add(self, rhs: Quaternion<T>) -> Self::Output {
+        Quaternion::new(
+            self.w + rhs.w,
+            self.x + rhs.x,
+            self.y + rhs.y,
+            self.z + rhs.z,
+        )
+    }
+}
+
+impl<T: Numeric<T>> Sub for Quaternion<T> {
+    type Output = Quaternion<T>;
+
+    #[inline]
+    fn sub(self, rhs: Quaternion<T>) -> Self::Output {
+        Quaternion::new(
+            self.w - rhs.w,
+            self.x - rhs.x,
+            self.y - rhs.y,
+            self.z - rhs.z,
+        )
+    }
+}
+
+impl<T: Numeric<T>> Mul for Quaternion<T> {
+    type Output = Quaternion<T>;
+
+    /// The Hamilton product
+    #[inline]
+    fn mul(self, rhs: Quaternion<T>) -> Self::Output {
+        let (a, b) = (self, rhs);
+        Quaternion::new(
+            a.w * b.w - a.x * b.x - a.y * b.y - a.z * b.z,
+            a.w * b.x + a.x * b.w + a.y * b.z - a.z * b.y,
+            a.w * b.y - a.x * b.z + a.y * b.w + a.z * b.x,
+            a.w * b.z + a.x * b.y - a.y * b.x + a.z * b.w,
+        )
+    }
+}
+
+impl<T: Numeric<T> + Neg<Output = T>> Neg for Quaternion<T> {
+    type Output = Quaternion<T>;
+
+    #[inline]
+    fn neg(self) -> Self::Output {
+        Quaternion::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+impl<T: Numeric<T>> AddAssign for Quaternion<T> {
+    #[inline]
+    fn add_assign(&mut self, rhs: Quaternion<T>) {
+        *self = *self + rhs;
+    }
+}
+
+impl<T: Numeric<T>> SubAssign for Quaternion<T> {
+    #[inline]
+    fn sub_assign(&mut self, rhs: Quaternion<T>) {
+        *self = *self - rhs;
+    }
+}
+
+impl<T: Numeric<T>> MulAssign for Quaternion<T> {
+    #[inline]
+    fn mul_assign(&mut self, rhs: Quaternion<T>) {
+        *self = *self * rhs;
+    }
+}
+
+#[cfg(test)]
+mod quaternion_tests {
+    use super::*;
+    use crate::assert_matrix_abs_diff_eq;
+    use crate::geometry::*;
+    use std::f64::consts::{FRAC_PI_2, PI};
+
+    const EPS: f64 = 1e-12;
+
+    fn assert_q_eq(a: Quaternion<f64>, b: Quaternion<f64>) {
+        let d = a - b;
+        assert!(d.norm() < EPS, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn test_hamilton_product() {
+        let i = Quaternion::new(0, 1, 0, 0);
+        let j = Quaternion::new(0, 0, 1, 0);
+        let k = Quaternion::new(0, 0, 0, 1);
+        let minus_one = Quaternion::new(-1, 0, 0, 0);
+        assert_eq!(i * i, minus_one);
+        assert_eq!(j * j, minus_one);
+        assert_eq!(k * k, minus_one);
+        assert_eq!(i * j * k, minus_one);
+        assert_eq!(i * j, k);
+        assert_eq!(j * i, -k);
+        let q = Quaternion::new(1, 2, 3, 4);
+        assert_eq!(q * q.conjugate(), Quaternion::new(30, 0, 0, 0));
+        assert_eq!(q.norm_squared(), 30);
+        let mut p = q;
+        p *= Quaternion::one();
+        p += q;
+        p -= Quaternion::new(1, 1, 1, 1);
+        assert_eq!(p, Quaternion::new(1, 3, 5, 7));
+    }
+
+    #[test]
+    fn test_quaternion_matrix_elements() {
+        let i = Quaternion::new(0.0, 1.0, 0.0, 0.0);
+        let j = Quaternion::new(0.0, 0.0, 1.0, 0.0);
+        let m = smatrix![i, j];
+        let n = svector![j, i];
+        assert_eq!((m * n)[0][0], i * j + j * i);
+    }
+
+    #[test]
+    fn test_normalize() {
+        let q = Quaternion::new(1.0, -1.0, 1.0, -1.0);
+        assert_eq!(q.norm(), 2.0);
+        assert_eq!(q.normalize(), Quaternion::new(0.5, -0.5, 0.5, -0.5));
+        let u = UnitQuaternion::new_normalize(q);
+        assert_eq!(u.quaternion().norm(), 1.0);
+    }
+
+    #[test]
+    fn test_rotation_matrix_round_trip() {
+        let rotations = [
+            SMatrix::one(),
+            rotation_x(PI),
+            rotation_y(PI),
+            rotation_z(PI),
+            rotation_axis_angle(&svector![1.0, -2.0, 0.5], 3.0),
+            rotation_euler(EulerOrder::ZYX, 0.3, -1.2, 2.9),
+            rotation_euler(EulerOrder::XYZ, PI, 0.0, 1e-9),
+        ];
+        for r in rotations.iter() {
+            let q = Quaternion::from_rotation_matrix(r);
+            assert!((q.norm() - 1.0).abs() < EPS);
+            assert!(q.w >= 0.0);
+            assert_matrix_abs_diff_eq!(q.to_rotation_matrix(), *r, EPS);
+        }
+    }
+
+    #[test]
+    fn test_unit_quaternion() {
+        let axis = svector![0.0, 0.0, 2.0];
+        let q = UnitQuaternion::from_axis_angle(&axis, FRAC_PI_2);
+        assert_matrix_abs_diff_eq!(q.to_rotation_matrix(), rotation_z(FRAC_PI_2), EPS);
+        let v = svector![1.0, 2.0, 3.0];
+        assert_matrix_abs_diff_eq!(q.rotate_vector(&v), svector![-2.0, 1.0, 3.0], EPS);
+        assert!((q.angle() - FRAC_PI_2).abs() < EPS);
+        assert_q_eq((q * q.inverse()).quaternion(), Quaternion::one());
+        let p = UnitQuaternion::from_axis_angle(&svector![1.0, 0.0, 0.0], 0.4);
+        // composition matches the product of the rotation matrices
+        assert_matrix_abs_diff_eq!(
+            (q * p).to_rotation_matrix(),
+            q.to_rotation_matrix() * p.to_rotation_matrix(),
+            EPS
+        );
+        let r = UnitQuaternion::from_rotation_matrix(&(q * p).to_rotation_matrix());
+        assert_matrix_abs_diff_eq!(r.rotate_vector(&v), (q * p).rotate_vector(&v), EPS);
+    }
+
+    #[test]
+    fn test_slerp() {
+        let axis = svector![1.0, 1.0, 0.0];
+        let a = UnitQuaternion::identity();
+        let b = UnitQuaternion::from_axis_angle(&axis, 2.0);
+        assert_q_eq(a.slerp(&b, 0.0).quaternion(), a.quaternion());
+        assert_q_eq(a.slerp(&b, 1.0).quaternion(), b.quaternion());
+        let half = UnitQuaternion::from_axis_angle(&axis, 1.0);
+        assert_q_eq(a.slerp(&b, 0.5).quaternion(), half.quaternion());
+        // the shorter arc is taken for -q as well
+        let minus_b = -b.quaternion();
+        assert_q_eq(a.quaternion().slerp(&minus_b, 0.5), half.quaternion());
+        // nearly identical rotations
+        let c = UnitQuaternion::from_axis_angle(&axis, 1e-9);
+        assert!((a.slerp(&c, 0.5).angle() - 5e-10).abs() < 1e-15);
+    }
+}
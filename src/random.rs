@@ -0,0 +1,592 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Random matrices
+//!
+//! The `random_*` constructors of [MF](MF) draw from any [MatrixRng](MatrixRng).
+//! The built-in [Xoshiro256PlusPlus](Xoshiro256PlusPlus) generator needs no
+//! dependency and always produces the same matrices for the same seed. With
+//! the `rand` feature enabled every `rand_core::RngCore` is a `MatrixRng`
+//! and `Xoshiro256PlusPlus` is an `RngCore` itself.
+//!
+//! ```
+//! use const_matrix::matrix::MF;
+//! use const_matrix::random::Xoshiro256PlusPlus;
+//!
+//! let mut rng = Xoshiro256PlusPlus::seed_from_u64(42);
+//! let a = MF::<f64, 3, 3>::random_uniform_stack(&mut rng, -1.0, 1.0);
+//! let mut rng = Xoshiro256PlusPlus::seed_from_u64(42);
+//! assert_eq!(MF::<f64, 3, 3>::random_uniform_heap(&mut rng, -1.0, 1.0), a);
+//! ```
+
+use crate::matrix::*;
+#[cfg(feature = "rand")]
+use std::convert::TryInto;
+use std::ops::Range;
+
+/// A source of uniformly distributed random `u64` values.
+pub trait MatrixRng {
+    /// Returns the next random `u64`.
+    fn next_u64(&mut self) -> u64;
+
+    /// Returns a random `f64` uniformly distributed in `[0, 1)`.
+    #[inline]
+    fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 * (1.0 / (1u64 << 53) as f64)
+    }
+}
+
+#[cfg(feature = "rand")]
+impl<R: rand_core::RngCore> MatrixRng for R {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        rand_core::RngCore::next_u64(self)
+    }
+}
+
+/// The xoshiro256++ generator of Blackman and Vigna. It is fast and has good
+/// statistical quality but is not cryptographically secure.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Xoshiro256PlusPlus {
+    s: [u64; 4],
+}
+
+#[inline]
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
+
+impl Xoshiro256PlusPlus {
+    /// Creates a generator whose state is expanded from `seed` with
+    /// splitmix64, so that similar seeds give unrelated sequences.
+    pub fn seed_from_u64(seed: u64) -> Self {
+        let mut state = seed;
+        Xoshiro256PlusPlus {
+            s: std::array::from_fn(|_| splitmix64(&mut state)),
+        }
+    }
+
+    #[inline]
+    fn step(&mut self) -> u64 {
+        let s = &mut self.s;
+        let result = s[0].wrapping_add(s[3]).rotate_left(23).wrapping_add(s[0]);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+}
+
+#[cfg(not(feature = "rand"))]
+impl MatrixRng for Xoshiro256PlusPlus {
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.step()
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::RngCore for Xoshiro256PlusPlus {
+    #[inline]
+    fn next_u32(&mut self) -> u32 {
+        (self.step() >> 32) as u32
+    }
+
+    #[inline]
+    fn next_u64(&mut self) -> u64 {
+        self.step()
+    }
+
+    #[inline]
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+
+    #[inline]
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+#[cfg(feature = "rand")]
+impl rand_core::SeedableRng for Xoshiro256PlusPlus {
+    type Seed = [u8; 32];
+
+    fn from_seed(seed: [u8; 32]) -> Self {
+        if seed == [0; 32] {
+            // the all-zero state is a fixed point
+            return Xoshiro256PlusPlus::seed_from_u64(0);
+        }
+        let mut s = [0; 4];
+        for (word, bytes) in s.iter_mut().zip(seed.chunks_exact(8)) {
+            *word = u64::from_le_bytes(bytes.try_into().unwrap());
+        }
+        Xoshiro256PlusPlus { s }
+    }
+
+    fn seed_from_u64(seed: u64) -> Self {
+        Xoshiro256PlusPlus::seed_from_u64(seed)
+    }
+}
+
+// Standard normal variates from the Box-Muller transform, two at a time
+struct Normal {
+    spare: Option<f64>,
+}
+
+impl Normal {
+    #[inline]
+    fn sample<R: MatrixRng>(&mut self, rng: &mut R) -> f64 {
+        if let Some(z) = self.spare.take() {
+            return z;
+        }
+        // 1 - u is in (0, 1] so that the logarithm is finite
+        let radius = (-2.0 * (1.0 - rng.next_f64()).ln()).sqrt();
+        let theta = 2.0 * std::f64::consts::PI * rng.next_f64();
+        self.spare = Some(radius * theta.sin());
+        radius * theta.cos()
+    }
+}
+
+#[inline]
+fn fill_with<T, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    mut f: impl FnMut() -> T,
+) {
+    for cell in a.iter_mut().flat_map(|row| row.iter_mut()) {
+        *cell = f();
+    }
+}
+
+#[inline]
+fn fill_normal<T: Real, R: MatrixRng, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    rng: &mut R,
+    mean: T,
+    std_dev: T,
+) {
+    let mut normal = Normal { spare: None };
+    fill_with(a, || mean + std_dev * T::from_f64(normal.sample(rng)));
+}
+
+#[inline]
+fn dot<T: Real, const N: usize>(x: &[T; N], y: &[T; N]) -> T {
+    x.iter()
+        .zip(y.iter())
+        .fold(T::default(), |acc, (&u, &v)| acc + u * v)
+}
+
+// Orthonormalizes the rows of a Gaussian matrix with modified Gram-Schmidt.
+// This is the Q of a QR decomposition with positive diagonal of R (of the
+// transpose), which makes it Haar distributed.
+fn orthogonal<T: Real, R: MatrixRng, const N: usize>(a: &mut [[T; N]; N], rng: &mut R) {
+    loop {
+        fill_normal(a, rng, T::default(), T::one());
+        if gram_schmidt(a) {
+            return;
+        }
+    }
+}
+
+fn gram_schmidt<T: Real, const N: usize>(a: &mut [[T; N]; N]) -> bool {
+    for i in 0..N {
+        let (done, rest) = a.split_at_mut(i);
+        let row = &mut rest[0];
+        for q in done.iter() {
+            let r = dot(q, row);
+            for (x, &y) in row.iter_mut().zip(q.iter()) {
+                *x -= r * y;
+            }
+        }
+        let norm = dot(row, row).sqrt();
+        // happens with probability zero, start over with new rows
+        if norm <= T::EPSILON {
+            return false;
+        }
+        for x in row.iter_mut() {
+            *x /= norm;
+        }
+    }
+    true
+}
+
+// G * G^T + N * I for a standard normal G
+fn spd<T: Real, R: MatrixRng, const N: usize>(a: &mut [[T; N]; N], rng: &mut R) {
+    let mut g = [[T::default(); N]; N];
+    fill_normal(&mut g, rng, T::default(), T::one());
+    let n = T::from_f64(N as f64);
+    for i in 0..N {
+        for j in 0..=i {
+            let mut x = dot(&g[i], &g[j]);
+            if i == j {
+                x += n;
+            }
+            a[i][j] = x;
+            a[j][i] = x;
+        }
+    }
+}
+
+/// A value in `[low, high)` if `low < high`. The product can round up to
+/// `high` itself (e.g. for `f32` whenever `u >= 1 - 2^-25`), which is then
+/// replaced by the largest value below `high`.
+#[inline]
+fn uniform<T: Real, R: MatrixRng>(rng: &mut R, low: T, high: T) -> T {
+    let x = low + (high - low) * T::from_f64(rng.next_f64());
+    if x >= high && low < high {
+        high.next_down()
+    } else {
+        x
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The primitive integer types, which the `random_integer_*` constructors
+/// draw. This trait is sealed, so that floats can't be drawn as integers.
+///
+/// ```compile_fail
+/// use const_matrix::matrix::MF;
+/// use const_matrix::random::Xoshiro256PlusPlus;
+///
+/// let mut rng = Xoshiro256PlusPlus::seed_from_u64(1);
+/// MF::<f64, 2, 2>::random_integer_stack(&mut rng, 0.5..3.7);
+/// ```
+pub trait RandomInt: Copy + PartialOrd + sealed::Sealed {
+    #[doc(hidden)]
+    fn to_i128(self) -> i128;
+    #[doc(hidden)]
+    fn from_i128(x: i128) -> Self;
+}
+
+macro_rules! impl_random_int {
+    ($($t:ty),*) => {
+        $(
+            impl sealed::Sealed for $t {}
+
+            impl RandomInt for $t {
+                // u128 values of 2^127 and above wrap around
+                #[inline]
+                fn to_i128(self) -> i128 {
+                    self as i128
+                }
+
+                #[inline]
+                fn from_i128(x: i128) -> Self {
+                    x as $t
+                }
+            }
+        )*
+    };
+}
+
+impl_random_int!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize);
+
+#[inline]
+fn fill_integer<T: RandomInt, R: MatrixRng, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    rng: &mut R,
+    range: Range<T>,
+) {
+    assert!(range.start < range.end, "the range must not be empty");
+    // u128 bounds wrap around in i128, so compute modulo 2^128
+    let start = range.start.to_i128();
+    let span = range.end.to_i128().wrapping_sub(start) as u128;
+    // reject the top values that would make the result biased
+    let zone = u128::MAX - (u128::MAX - span + 1) % span;
+    fill_with(a, || loop {
+        let x = (u128::from(rng.next_u64()) << 64) | u128::from(rng.next_u64());
+        if x <= zone {
+            return T::from_i128(start.wrapping_add((x % span) as i128));
+        }
+    });
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> MF<T, ROWS, COLS> {
+    /// Create a matrix with entries uniformly distributed in `[low, high)`
+    /// on the stack.
+    #[inline]
+    pub fn random_uniform_stack<R: MatrixRng>(
+        rng: &mut R,
+        low: T,
+        high: T,
+    ) -> SMatrix<T, ROWS, COLS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        fill_with(m.array_mut(), || uniform(rng, low, high));
+        m
+    }
+
+    /// Create a matrix with entries uniformly distributed in `[low, high)`
+    /// on the heap.
+    #[inline]
+    pub fn random_uniform_heap<R: MatrixRng>(rng: &mut R, low: T, high: T) -> HMatrix<T, ROWS, COLS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        fill_with(m.array_mut(), || uniform(rng, low, high));
+        m
+    }
+
+    /// Create a matrix with normally distributed entries on the stack.
+    #[inline]
+    pub fn random_normal_stack<R: MatrixRng>(
+        rng: &mut R,
+        mean: T,
+        std_dev: T,
+    ) -> SMatrix<T, ROWS, COLS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        fill_normal(m.array_mut(), rng, mean, std_dev);
+        m
+    }
+
+    /// Create a matrix with normally distributed entries on the heap.
+    #[inline]
+    pub fn random_normal_heap<R: MatrixRng>(
+        rng: &mut R,
+        mean: T,
+        std_dev: T,
+    ) -> HMatrix<T, ROWS, COLS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        fill_normal(m.array_mut(), rng, mean, std_dev);
+        m
+    }
+
+    /// Create a random orthogonal matrix, distributed uniformly (Haar) over
+    /// all orthogonal matrices, on the stack. It is computed by a QR
+    /// decomposition of a matrix with standard normal entries.
+    pub fn random_orthogonal_stack<R: MatrixRng>(rng: &mut R) -> SMatrix<T, ROWS, ROWS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, ROWS>::new_stack();
+        orthogonal(m.array_mut(), rng);
+        m
+    }
+
+    /// Create a random orthogonal matrix, distributed uniformly (Haar) over
+    /// all orthogonal matrices, on the heap.
+    pub fn random_orthogonal_heap<R: MatrixRng>(rng: &mut R) -> HMatrix<T, ROWS, ROWS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, ROWS>::new_heap();
+        orthogonal(m.array_mut(), rng);
+        m
+    }
+
+    /// Create a random symmetric positive definite matrix `G Gᵀ + ROWS I`,
+    /// where `G` has standard normal entries, on the stack.
+    pub fn random_spd_stack<R: MatrixRng>(rng: &mut R) -> SMatrix<T, ROWS, ROWS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, ROWS>::new_stack();
+        spd(m.array_mut(), rng);
+        m
+    }
+
+    /// Create a random symmetric positive definite matrix `G Gᵀ + ROWS I`,
+    /// where `G` has standard normal entries, on the heap.
+    pub fn random_spd_heap<R: MatrixRng>(rng: &mut R) -> HMatrix<T, ROWS, ROWS>
+    where
+        T: Real,
+    {
+        let mut m = MF::<T, ROWS, ROWS>::new_heap();
+        spd(m.array_mut(), rng);
+        m
+    }
+
+    /// Create a matrix of integers drawn uniformly from the half-open
+    /// `range` on the stack. Panics if the range is empty.
+    ///
+    /// ```
+    /// use const_matrix::matrix::MF;
+    /// use const_matrix::random::Xoshiro256PlusPlus;
+    ///
+    /// let mut rng = Xoshiro256PlusPlus::seed_from_u64(7);
+    /// let dice = MF::<u8, 10, 10>::random_integer_stack(&mut rng, 1..7);
+    /// assert!(dice.as_ref().iter().flatten().all(|&x| (1..7).contains(&x)));
+    /// ```
+    #[inline]
+    pub fn random_integer_stack<R: MatrixRng>(
+        rng: &mut R,
+        range: Range<T>,
+    ) -> SMatrix<T, ROWS, COLS>
+    where
+        T: RandomInt,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_stack();
+        fill_integer(m.array_mut(), rng, range);
+        m
+    }
+
+    /// Create a matrix of integers drawn uniformly from the half-open
+    /// `range` on the heap. Panics if the range is empty.
+    #[inline]
+    pub fn random_integer_heap<R: MatrixRng>(rng: &mut R, range: Range<T>) -> HMatrix<T, ROWS, COLS>
+    where
+        T: RandomInt,
+    {
+        let mut m = MF::<T, ROWS, COLS>::new_heap();
+        fill_integer(m.array_mut(), rng, range);
+        m
+    }
+}
+
+#[cfg(test)]
+mod random_tests {
+    use super::*;
+
+    fn cells<T, const ROWS: usize, const COLS: usize>(
+        a: &[[T; COLS]; ROWS],
+    ) -> impl Iterator<Item = &T> {
+        a.iter().flatten()
+    }
+
+    #[test]
+    fn test_reference_values() {
+        // first splitmix64 outputs for seed 0
+        let mut state = 0;
+        assert_eq!(splitmix64(&mut state), 0xe220_a839_7b1d_cdaf);
+        assert_eq!(splitmix64(&mut state), 0x6e78_9e6a_a1b9_65f4);
+        // reference outputs of xoshiro256++ for the state [1, 2, 3, 4]
+        let mut r = Xoshiro256PlusPlus { s: [1, 2, 3, 4] };
+        let v: Vec<u64> = (0..4).map(|_| r.step()).collect();
+        assert_eq!(v, [41943041, 58720359, 3588806011781223, 3591011842654386]);
+        let mut a = Xoshiro256PlusPlus::seed_from_u64(1);
+        let mut b = a.clone();
+        let first: Vec<u64> = (0..4).map(|_| MatrixRng::next_u64(&mut a)).collect();
+        let again: Vec<u64> = (0..4).map(|_| MatrixRng::next_u64(&mut b)).collect();
+        assert_eq!(first, again);
+        assert_ne!(first[0], first[1]);
+        let mut c = Xoshiro256PlusPlus::seed_from_u64(2);
+        assert_ne!(MatrixRng::next_u64(&mut c), first[0]);
+    }
+
+    #[test]
+    fn test_uniform_normal() {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(3);
+        let u = MF::<f64, 20, 20>::random_uniform_heap(&mut rng, 2.0, 5.0);
+        assert!(cells(u.array()).all(|&x| (2.0..5.0).contains(&x)));
+        let mean = cells(u.array()).sum::<f64>() / 400.0;
+        assert!((mean - 3.5).abs() < 0.2);
+        let n = MF::<f64, 50, 50>::random_normal_heap(&mut rng, 1.0, 2.0);
+        let mean = cells(n.array()).sum::<f64>() / 2500.0;
+        let var = cells(n.array())
+            .map(|x| (x - mean) * (x - mean))
+            .sum::<f64>()
+            / 2499.0;
+        assert!((mean - 1.0).abs() < 0.2);
+        assert!((var - 4.0).abs() < 0.5);
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(3);
+        let u32s = MF::<f32, 2, 2>::random_uniform_stack(&mut rng, 0.0, 1.0);
+        assert!(cells(u32s.array()).all(|&x| (0.0..1.0).contains(&x)));
+    }
+
+    #[test]
+    fn test_orthogonal_spd() {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(4);
+        let q = MF::<f64, 4, 4>::random_orthogonal_stack(&mut rng);
+        let qqt = q * q.trans();
+        let id = MF::<f64, 4, 4>::unit_stack();
+        for (x, y) in cells(qqt.array()).zip(cells(id.array())) {
+            assert!((x - y).abs() < 1e-12);
+        }
+        let s = MF::<f64, 3, 3>::random_spd_heap(&mut rng);
+        assert_eq!(s, s.trans());
+        // all leading principal minors are positive
+        assert!(s[0][0] > 0.0);
+        assert!(s[0][0] * s[1][1] - s[0][1] * s[1][0] > 0.0);
+        let det = s[0][0] * (s[1][1] * s[2][2] - s[1][2] * s[2][1])
+            - s[0][1] * (s[1][0] * s[2][2] - s[1][2] * s[2][0])
+            + s[0][2] * (s[1][0] * s[2][1] - s[1][1] * s[2][0]);
+        assert!(det > 0.0);
+        let q = MF::<f32, 3, 3>::random_orthogonal_heap(&mut rng);
+        let qtq = q.trans() * &q;
+        let id = MF::<f32, 3, 3>::unit_stack();
+        for (x, y) in cells(qtq.array()).zip(cells(id.array())) {
+            assert!((x - y).abs() < 1e-5);
+        }
+    }
+
+    struct MaxRng;
+
+    impl MatrixRng for MaxRng {
+        fn next_u64(&mut self) -> u64 {
+            u64::MAX
+        }
+    }
+
+    #[test]
+    fn test_uniform_excludes_high() {
+        let m = MF::<f32, 2, 2>::random_uniform_stack(&mut MaxRng, 0.0, 1.0);
+        assert!(cells(m.array()).all(|&x| x == Real::next_down(1.0f32)));
+        let m = MF::<f64, 2, 2>::random_uniform_heap(&mut MaxRng, -3.0, 5.0);
+        assert!(cells(m.array()).all(|&x| x < 5.0 && x > 4.999));
+        let m = MF::<f32, 1, 1>::random_uniform_heap(&mut MaxRng, -1.0, 0.0);
+        assert!(m[0][0] < 0.0);
+    }
+
+    #[test]
+    fn test_integer() {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(5);
+        let m = MF::<i32, 10, 10>::random_integer_heap(&mut rng, -3..3);
+        assert!(cells(m.array()).all(|&x| (-3..3).contains(&x)));
+        for k in -3..3 {
+            assert!(cells(m.array()).any(|&x| x == k));
+        }
+        let m = MF::<i64, 4, 4>::random_integer_stack(&mut rng, i64::MIN..i64::MAX);
+        assert_ne!(m[0][0], m[0][1]);
+        let one = MF::<u8, 3, 3>::random_integer_stack(&mut rng, 9..10);
+        assert!(cells(one.array()).all(|&x| x == 9));
+    }
+
+    #[test]
+    fn test_integer_full_width() {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(7);
+        let m = MF::<i128, 8, 8>::random_integer_heap(&mut rng, i128::MIN..i128::MAX);
+        assert!(cells(m.array()).any(|&x| x < 0) && cells(m.array()).any(|&x| x > 0));
+        assert!(cells(m.array()).all(|&x| x != i128::MAX));
+        let range = 2u128..(1u128 << 127) + 1;
+        let m = MF::<u128, 8, 8>::random_integer_stack(&mut rng, range.clone());
+        assert!(cells(m.array()).all(|x| range.contains(x)));
+        assert!(cells(m.array()).any(|&x| x > 1u128 << 126));
+        let top = u128::MAX - 2..u128::MAX;
+        let m = MF::<u128, 4, 4>::random_integer_heap(&mut rng, top.clone());
+        assert!(cells(m.array()).all(|x| top.contains(x)));
+        let m = MF::<u128, 4, 4>::random_integer_stack(&mut rng, 0..u128::MAX);
+        assert!(cells(m.array()).any(|&x| x > 1u128 << 127));
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn test_empty_range() {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(6);
+        MF::<i32, 2, 2>::random_integer_stack(&mut rng, 3..3);
+    }
+}
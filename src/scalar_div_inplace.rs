@@ -0,0 +1,96 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Inplace scalar division implemented as DivAssign
+
+use crate::matrix::*;
+use std::ops::DivAssign;
+
+#[inline]
+fn div_assign_scalar<T: Numeric<T> + DivAssign, const ROWS: usize, const COLS: usize>(
+    a: &mut [[T; COLS]; ROWS],
+    scalar: T,
+) {
+    for row in a.iter_mut().take(ROWS) {
+        for cell in row.iter_mut().take(COLS) {
+            *cell /= scalar;
+        }
+    }
+}
+
+// A0) SMatrix /= Numeric<T>
+/// Inplace scalar division for [SMatrix](SMatrix) implemented as `DivAssign`.
+impl<T: Numeric<T> + DivAssign, const ROWS: usize, const COLS: usize> DivAssign<T>
+    for SMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn div_assign(&mut self, rhs: T) {
+        div_assign_scalar(self.array_mut(), rhs);
+    }
+}
+
+// B0) HMatrix /= Numeric<T>
+/// Inplace scalar division for [HMatrix](HMatrix) implemented as `DivAssign`.
+impl<T: Numeric<T> + DivAssign, const ROWS: usize, const COLS: usize> DivAssign<T>
+    for HMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn div_assign(&mut self, rhs: T) {
+        div_assign_scalar(self.array_mut(), rhs);
+    }
+}
+
+// A1) &mut SMatrix /= Numeric<T>
+/// Inplace scalar division for a `&mut` [SMatrix](SMatrix) implemented as `DivAssign`.
+impl<T: Numeric<T> + DivAssign, const ROWS: usize, const COLS: usize> DivAssign<T>
+    for &mut SMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn div_assign(&mut self, rhs: T) {
+        div_assign_scalar(self.array_mut(), rhs);
+    }
+}
+
+// B1) &mut HMatrix /= Numeric<T>
+/// Inplace scalar division for a `&mut` [HMatrix](HMatrix) implemented as `DivAssign`.
+impl<T: Numeric<T> + DivAssign, const ROWS: usize, const COLS: usize> DivAssign<T>
+    for &mut HMatrix<T, ROWS, COLS>
+{
+    #[inline]
+    fn div_assign(&mut self, rhs: T) {
+        div_assign_scalar(self.array_mut(), rhs);
+    }
+}
+
+#[cfg(test)]
+mod scalar_div_inplace_tests {
+    #[test]
+    fn test_1_smatrix() {
+        let mut a = smatrix![6.0f32, 0.0; 0.0, 12.0];
+        a /= 3.0f32;
+        assert_eq!(a, smatrix![2.0f32, 0.0; 0.0, 4.0]);
+        let mut a = smatrix![7i8, -7; 0, 12];
+        a /= 2i8;
+        assert_eq!(a, smatrix![3i8, -3; 0, 6]);
+        let mut b = &mut smatrix![1.0f64, 3.0];
+        b /= 4.0f64;
+        assert_eq!(*b, smatrix![0.25f64, 0.75]);
+    }
+
+    #[test]
+    fn test_1_hmatrix() {
+        let mut a = hmatrix![6.0f64, 0.0; 0.0, 12.0];
+        a /= 3.0f64;
+        assert_eq!(a, smatrix![2.0f64, 0.0; 0.0, 4.0]);
+        let mut a = hmatrix![255u8, 10];
+        a /= 5u8;
+        assert_eq!(a, smatrix![51u8, 2]);
+        let mut b = &mut hmatrix![9i128, -27];
+        b /= 9i128;
+        assert_eq!(*b, smatrix![1i128, -3]);
+    }
+}
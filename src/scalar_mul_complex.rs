@@ -0,0 +1,127 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Scalar multiplication
+
+use crate::complex::Complex;
+use crate::matrix::*;
+use std::ops::Mul;
+
+// Complex is a local type, so unlike for the primitive scalars one
+// generic implementation covers all element types.
+
+#[inline]
+fn scalar_mul_complex<T: Numeric<T>, const ROWS: usize, const COLS: usize>(
+    scalar: Complex<T>,
+    a: &[[Complex<T>; COLS]; ROWS],
+    b: &mut [[Complex<T>; COLS]; ROWS],
+) {
+    for row in 0..ROWS {
+        for col in 0..COLS {
+            b[row][col] = scalar * a[row][col];
+        }
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Mul<SMatrix<Complex<T>, ROWS, COLS>>
+    for Complex<T>
+{
+    type Output = SMatrix<Complex<T>, ROWS, COLS>;
+    #[inline]
+    fn mul(self, rhs: SMatrix<Complex<T>, ROWS, COLS>) -> Self::Output {
+        let mut b = MF::<Complex<T>, ROWS, COLS>::new_stack();
+        scalar_mul_complex(self, rhs.array(), b.array_mut());
+        b
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Mul<HMatrix<Complex<T>, ROWS, COLS>>
+    for Complex<T>
+{
+    type Output = HMatrix<Complex<T>, ROWS, COLS>;
+    #[inline]
+    fn mul(self, rhs: HMatrix<Complex<T>, ROWS, COLS>) -> Self::Output {
+        let mut b = MF::<Complex<T>, ROWS, COLS>::new_heap();
+        scalar_mul_complex(self, rhs.array(), b.array_mut());
+        b
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Mul<&SMatrix<Complex<T>, ROWS, COLS>>
+    for Complex<T>
+{
+    type Output = SMatrix<Complex<T>, ROWS, COLS>;
+    #[inline]
+    fn mul(self, rhs: &SMatrix<Complex<T>, ROWS, COLS>) -> Self::Output {
+        let mut b = MF::<Complex<T>, ROWS, COLS>::new_stack();
+        scalar_mul_complex(self, rhs.array(), b.array_mut());
+        b
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Mul<&HMatrix<Complex<T>, ROWS, COLS>>
+    for Complex<T>
+{
+    type Output = HMatrix<Complex<T>, ROWS, COLS>;
+    #[inline]
+    fn mul(self, rhs: &HMatrix<Complex<T>, ROWS, COLS>) -> Self::Output {
+        let mut b = MF::<Complex<T>, ROWS, COLS>::new_heap();
+        scalar_mul_complex(self, rhs.array(), b.array_mut());
+        b
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Mul<&mut SMatrix<Complex<T>, ROWS, COLS>>
+    for Complex<T>
+{
+    type Output = SMatrix<Complex<T>, ROWS, COLS>;
+    #[inline]
+    fn mul(self, rhs: &mut SMatrix<Complex<T>, ROWS, COLS>) -> Self::Output {
+        let mut b = MF::<Complex<T>, ROWS, COLS>::new_stack();
+        scalar_mul_complex(self, rhs.array(), b.array_mut());
+        b
+    }
+}
+
+impl<T: Numeric<T>, const ROWS: usize, const COLS: usize> Mul<&mut HMatrix<Complex<T>, ROWS, COLS>>
+    for Complex<T>
+{
+    type Output = HMatrix<Complex<T>, ROWS, COLS>;
+    #[inline]
+    fn mul(self, rhs: &mut HMatrix<Complex<T>, ROWS, COLS>) -> Self::Output {
+        let mut b = MF::<Complex<T>, ROWS, COLS>::new_heap();
+        scalar_mul_complex(self, rhs.array(), b.array_mut());
+        b
+    }
+}
+
+#[cfg(test)]
+mod scalar_mul_tests {
+    use super::*;
+
+    #[test]
+    fn test_1() {
+        let i = Complex::new(0, 1);
+        let a = smatrix![Complex::new(1, 1), Complex::new(2, 0)];
+        let expected = smatrix![Complex::new(-1, 1), Complex::new(0, 2)];
+        assert_eq!(i * a, expected);
+        assert_eq!(i * a.to_heap(), expected);
+        assert_eq!(i * &a, expected);
+        assert_eq!(i * &a.to_heap(), expected);
+    }
+
+    #[test]
+    fn test_2() {
+        let two = Complex::new(2.0, 0.0);
+        let mut a = MF::<Complex<f64>, 2, 2>::unit_stack();
+        let b = two * &mut a;
+        assert_eq!(b, MF::<Complex<f64>, 2, 2>::diag_stack(two));
+        let mut a = MF::<Complex<f64>, 2, 2>::unit_heap();
+        let b = two * &mut a;
+        assert_eq!(b, MF::<Complex<f64>, 2, 2>::diag_heap(two));
+    }
+}
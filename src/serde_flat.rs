@@ -0,0 +1,283 @@
+// Copyright 2022 Stefan Zobel
+//
+// Licensed under the Apache License, Version 2.0, <LICENSE-APACHE or
+// http://apache.org/licenses/LICENSE-2.0> or the MIT license <LICENSE-MIT or
+// http://opensource.org/licenses/MIT>, at your option. This file may not be
+// copied, modified, or distributed except according to those terms.
+
+//! Flat-with-shape serialization (behind the `serde` feature)
+//!
+//! Serializes a matrix as a struct with the fields `rows`, `cols` and
+//! `data`, where `data` holds all elements in row-major order, e.g.
+//! `{"rows":2,"cols":2,"data":[1,2,3,4]}` in JSON. This avoids the nested
+//! sequences of the default representation, which is more compact in binary
+//! formats. Deserialization fails if the shape differs from `ROWS x COLS`
+//! or if `data` doesn't have `ROWS * COLS` elements. Use it with
+//! `#[serde(with = "const_matrix::serde_flat")]`:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Config {
+//!     #[serde(with = "const_matrix::serde_flat")]
+//!     weights: SMatrix<f32, 4, 4>,
+//! }
+//! ```
+
+use crate::matrix::*;
+use serde::de::{
+    Deserialize, DeserializeSeed, Deserializer, Error, IgnoredAny, MapAccess, SeqAccess, Visitor,
+};
+use serde::ser::{Serialize, SerializeSeq, SerializeStruct, Serializer};
+use std::convert::TryFrom;
+use std::fmt::{self, Formatter};
+use std::marker::PhantomData;
+
+/// The matrix types that can be (de)serialized flat, i.e.,
+/// [SMatrix](SMatrix) and [HMatrix](HMatrix).
+pub trait FlatMatrix<T, const ROWS: usize, const COLS: usize>:
+    AsRef<[[T; COLS]; ROWS]> + Sized
+{
+    #[doc(hidden)]
+    fn from_row_vec(rows: Vec<[T; COLS]>) -> Self;
+}
+
+impl<T, const ROWS: usize, const COLS: usize> FlatMatrix<T, ROWS, COLS> for SMatrix<T, ROWS, COLS> {
+    fn from_row_vec(rows: Vec<[T; COLS]>) -> Self {
+        SMatrix::new(<[[T; COLS]; ROWS]>::try_from(rows).unwrap_or_else(|_| unreachable!()))
+    }
+}
+
+impl<T, const ROWS: usize, const COLS: usize> FlatMatrix<T, ROWS, COLS> for HMatrix<T, ROWS, COLS> {
+    fn from_row_vec(rows: Vec<[T; COLS]>) -> Self {
+        HMatrix::from_rows(rows.into_iter())
+    }
+}
+
+struct Flat<'a, T, const ROWS: usize, const COLS: usize>(&'a [[T; COLS]; ROWS]);
+
+impl<T: Serialize, const ROWS: usize, const COLS: usize> Serialize for Flat<'_, T, ROWS, COLS> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(ROWS * COLS))?;
+        for x in self.0.iter().flat_map(|row| row.iter()) {
+            seq.serialize_element(x)?;
+        }
+        seq.end()
+    }
+}
+
+/// Serializes `matrix` as `rows`, `cols` and the row-major `data`.
+pub fn serialize<T, M, S, const ROWS: usize, const COLS: usize>(
+    matrix: &M,
+    serializer: S,
+) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    M: FlatMatrix<T, ROWS, COLS>,
+    S: Serializer,
+{
+    let mut s = serializer.serialize_struct("Matrix", 3)?;
+    s.serialize_field("rows", &ROWS)?;
+    s.serialize_field("cols", &COLS)?;
+    s.serialize_field("data", &Flat(matrix.as_ref()))?;
+    s.end()
+}
+
+const FIELDS: &[&str] = &["rows", "cols", "data"];
+
+enum Field {
+    Rows,
+    Cols,
+    Data,
+}
+
+struct FieldVisitor;
+
+impl Visitor<'_> for FieldVisitor {
+    type Value = Field;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("`rows`, `cols` or `data`")
+    }
+
+    fn visit_str<E: Error>(self, value: &str) -> Result<Field, E> {
+        match value {
+            "rows" => Ok(Field::Rows),
+            "cols" => Ok(Field::Cols),
+            "data" => Ok(Field::Data),
+            _ => Err(E::unknown_field(value, FIELDS)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Field {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_identifier(FieldVisitor)
+    }
+}
+
+// Reads at most `ROWS * COLS` elements, so that an oversized `data` fails
+// on its first extra element instead of being buffered completely
+struct DataSeed<T, const ROWS: usize, const COLS: usize>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>, const ROWS: usize, const COLS: usize> Visitor<'de>
+    for DataSeed<T, ROWS, COLS>
+{
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{} elements", ROWS * COLS)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut data = Vec::with_capacity(ROWS * COLS);
+        while data.len() < ROWS * COLS {
+            match seq.next_element()? {
+                Some(x) => data.push(x),
+                None => return Ok(data),
+            }
+        }
+        if seq.next_element::<IgnoredAny>()?.is_some() {
+            return Err(A::Error::invalid_length(ROWS * COLS + 1, &self));
+        }
+        Ok(data)
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const ROWS: usize, const COLS: usize> DeserializeSeed<'de>
+    for DataSeed<T, ROWS, COLS>
+{
+    type Value = Vec<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+struct FlatVisitor<T, const ROWS: usize, const COLS: usize>(PhantomData<T>);
+
+impl<T, const ROWS: usize, const COLS: usize> FlatVisitor<T, ROWS, COLS> {
+    fn into_rows<E: Error>(rows: usize, cols: usize, data: Vec<T>) -> Result<Vec<[T; COLS]>, E> {
+        if (rows, cols) != (ROWS, COLS) {
+            return Err(E::custom(format_args!(
+                "expected a {}x{} matrix, found shape {}x{}",
+                ROWS, COLS, rows, cols
+            )));
+        }
+        if data.len() != ROWS * COLS {
+            return Err(E::invalid_length(
+                data.len(),
+                &format!("{} elements", ROWS * COLS).as_str(),
+            ));
+        }
+        let mut it = data.into_iter();
+        Ok((0..ROWS)
+            .map(|_| std::array::from_fn(|_| it.next().unwrap()))
+            .collect())
+    }
+}
+
+impl<'de, T: Deserialize<'de>, const ROWS: usize, const COLS: usize> Visitor<'de>
+    for FlatVisitor<T, ROWS, COLS>
+{
+    type Value = Vec<[T; COLS]>;
+
+    fn expecting(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "a {}x{} matrix with rows, cols and data", ROWS, COLS)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let rows = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(0, &self))?;
+        let cols = seq
+            .next_element()?
+            .ok_or_else(|| A::Error::invalid_length(1, &self))?;
+        let data = seq
+            .next_element_seed(DataSeed::<T, ROWS, COLS>(PhantomData))?
+            .ok_or_else(|| A::Error::invalid_length(2, &self))?;
+        Self::into_rows(rows, cols, data)
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+        let (mut rows, mut cols, mut data) = (None, None, None);
+        while let Some(key) = map.next_key()? {
+            match key {
+                Field::Rows if rows.is_none() => rows = Some(map.next_value()?),
+                Field::Cols if cols.is_none() => cols = Some(map.next_value()?),
+                Field::Data if data.is_none() => {
+                    data = Some(map.next_value_seed(DataSeed::<T, ROWS, COLS>(PhantomData))?)
+                }
+                Field::Rows => return Err(A::Error::duplicate_field("rows")),
+                Field::Cols => return Err(A::Error::duplicate_field("cols")),
+                Field::Data => return Err(A::Error::duplicate_field("data")),
+            }
+        }
+        Self::into_rows(
+            rows.ok_or_else(|| A::Error::missing_field("rows"))?,
+            cols.ok_or_else(|| A::Error::missing_field("cols"))?,
+            data.ok_or_else(|| A::Error::missing_field("data"))?,
+        )
+    }
+}
+
+/// Deserializes a matrix from `rows`, `cols` and the row-major `data`.
+pub fn deserialize<'de, T, M, D, const ROWS: usize, const COLS: usize>(
+    deserializer: D,
+) -> Result<M, D::Error>
+where
+    T: Deserialize<'de>,
+    M: FlatMatrix<T, ROWS, COLS>,
+    D: Deserializer<'de>,
+{
+    let rows = deserializer.deserialize_struct(
+        "Matrix",
+        FIELDS,
+        FlatVisitor::<T, ROWS, COLS>(PhantomData),
+    )?;
+    Ok(M::from_row_vec(rows))
+}
+
+#[cfg(test)]
+mod serde_flat_tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let s = smatrix![1, 2; 3, 4; 5, 6];
+        let mut json = Vec::new();
+        serialize(&s, &mut serde_json::Serializer::new(&mut json)).unwrap();
+        let json = String::from_utf8(json).unwrap();
+        assert_eq!(json, r#"{"rows":3,"cols":2,"data":[1,2,3,4,5,6]}"#);
+        let back: SMatrix<i32, 3, 2> =
+            deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+        assert_eq!(back, s);
+        let h: HMatrix<i32, 3, 2> =
+            deserialize(&mut serde_json::Deserializer::from_str(&json)).unwrap();
+        assert_eq!(h, s);
+        // the fields may come in any order
+        let json = r#"{"data":[1,2,3,4,5,6],"cols":2,"rows":3}"#;
+        let h: HMatrix<i32, 3, 2> =
+            deserialize(&mut serde_json::Deserializer::from_str(json)).unwrap();
+        assert_eq!(h, s);
+    }
+
+    #[test]
+    fn test_errors() {
+        fn err(json: &str) -> String {
+            let r: Result<SMatrix<f64, 2, 2>, _> =
+                deserialize(&mut serde_json::Deserializer::from_str(json));
+            r.unwrap_err().to_string()
+        }
+        assert!(err(r#"{"rows":1,"cols":4,"data":[1,2,3,4]}"#)
+            .starts_with("expected a 2x2 matrix, found shape 1x4"));
+        assert!(err(r#"{"rows":2,"cols":2,"data":[1,2,3]}"#)
+            .starts_with("invalid length 3, expected 4 elements"));
+        assert!(err(r#"{"rows":2,"cols":2}"#).starts_with("missing field `data`"));
+        assert!(err(r#"{"rows":2,"cols":2,"data":[],"x":1}"#).starts_with("unknown field `x`"));
+        assert!(err(r#"{"rows":2,"rows":2}"#).starts_with("duplicate field `rows`"));
+        // stops at the first extra element, the rest is never read
+        assert!(err(r#"{"rows":2,"cols":2,"data":[1,2,3,4,5,"not a number"#)
+            .starts_with("invalid length 5, expected 4 elements"));
+        assert!(err(r#"[2,2,[1,2,3,4,5]]"#).starts_with("invalid length 5, expected 4 elements"));
+    }
+}